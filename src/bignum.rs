@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// each limb holds nine decimal digits, which keeps printing simple
+const BASE: u64 = 1_000_000_000;
+
+/// An integer of any size, for arithmetic that no longer fits in an i64.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    // least significant limb first, with no zero limbs at the end, so zero has none
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn from_i64(n: i64) -> BigInt {
+        let mut magnitude = n.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        BigInt {
+            negative: n < 0,
+            limbs,
+        }
+    }
+
+    /// Parses an optionally signed string of decimal digits.
+    pub fn parse(s: &str) -> Option<BigInt> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut limbs = Vec::new();
+        let mut end = digits.len();
+        while end > 0 {
+            let start = end.saturating_sub(9);
+            limbs.push(digits[start..end].parse().ok()?);
+            end = start;
+        }
+        Some(BigInt::new(negative, limbs))
+    }
+
+    /// Parses an optionally signed string of digits in the given radix, such as
+    /// the digits of a 0x literal with radix 16.
+    pub fn parse_radix(s: &str, radix: u32) -> Option<BigInt> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        let base = BigInt::from_i64(radix as i64);
+        let mut magnitude = BigInt::from_i64(0);
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            magnitude = magnitude.mul(&base).add(&BigInt::from_i64(digit as i64));
+        }
+        Some(if negative { magnitude.neg() } else { magnitude })
+    }
+
+    fn new(negative: bool, mut limbs: Vec<u32>) -> BigInt {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        // three limbs already reach 10^27, past anything an i64 can hold
+        if self.limbs.len() > 3 {
+            return None;
+        }
+        let mut magnitude: i128 = 0;
+        for limb in self.limbs.iter().rev() {
+            magnitude = magnitude * BASE as i128 + *limb as i128;
+        }
+        let n = if self.negative { -magnitude } else { magnitude };
+        i64::try_from(n).ok()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, limb| acc * BASE as f64 + *limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs.clone())
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::new(false, self.limbs.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_limbs(&self.limbs, &other.limbs));
+        }
+        // opposite signs subtract the smaller magnitude from the larger
+        match cmp_limbs(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::new(other.negative, sub_limbs(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, sub_limbs(&self.limbs, &other.limbs)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        let mut result = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = result[i + j] + a as u64 * b as u64 + carry;
+                result[i + j] = product % BASE;
+                carry = product / BASE;
+            }
+            result[i + other.limbs.len()] += carry;
+        }
+        let limbs = result.into_iter().map(|limb| limb as u32).collect();
+        BigInt::new(self.negative != other.negative, limbs)
+    }
+
+    /// Divides truncating toward zero, so the remainder takes the sign of
+    /// self. Gives None when other is zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let divisor = other.abs();
+        let mut quotient = vec![0u32; self.limbs.len()];
+        let mut remainder = BigInt::from_i64(0);
+        for (i, &limb) in self.limbs.iter().enumerate().rev() {
+            let mut limbs = vec![limb];
+            limbs.extend(remainder.limbs);
+            remainder = BigInt::new(false, limbs);
+            // the largest digit whose multiple of the divisor still fits
+            let (mut low, mut high) = (0, BASE as u32 - 1);
+            while low < high {
+                let mid = low + (high - low).div_ceil(2);
+                if divisor.mul(&BigInt::from_i64(mid as i64)) <= remainder {
+                    low = mid;
+                } else {
+                    high = mid - 1;
+                }
+            }
+            quotient[i] = low;
+            remainder = remainder.sub(&divisor.mul(&BigInt::from_i64(low as i64)));
+        }
+        Some((
+            BigInt::new(self.negative != other.negative, quotient),
+            BigInt::new(self.negative, remainder.limbs),
+        ))
+    }
+}
+
+fn cmp_limbs(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+// a - b, where a is at least as large as b
+fn sub_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut borrow = 0;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut diff = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += BASE as i64;
+            borrow = 1;
+        }
+        result.push(diff as u32);
+    }
+    result
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_limbs(&self.limbs, &other.limbs),
+            (true, true) => cmp_limbs(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        match limbs.next() {
+            None => write!(f, "0"),
+            Some(first) => {
+                if self.negative {
+                    write!(f, "-")?;
+                }
+                write!(f, "{}", first)?;
+                for limb in limbs {
+                    write!(f, "{:09}", limb)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
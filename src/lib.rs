@@ -0,0 +1,3078 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+
+mod bignum;
+mod ratio;
+
+pub use bignum::BigInt;
+pub use ratio::Ratio;
+
+#[derive(Debug)]
+pub enum RispError {
+    LexError(String),
+    ParseError(String),
+    RuntimeError(String),
+    /// A runtime error raised in a form read from source, with where that form is.
+    RuntimeErrorAt(String, Span),
+    /// Raised by `(exit n)`, unwinding evaluation so the caller can exit with status n.
+    Exit(i32),
+}
+
+impl fmt::Display for RispError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RispError::LexError(msg) => write!(f, "Lex error: {}", msg),
+            RispError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            RispError::RuntimeError(msg) => write!(f, "Runtime error: {}", msg),
+            RispError::RuntimeErrorAt(msg, span) => {
+                write!(f, "Runtime error at {}: {}", span.position, msg)?;
+                // the offending line, with a caret under the start of the form
+                if !span.line.is_empty() {
+                    write!(f, "\n{}\n{:>2$}", span.line, "^", span.position.column)?;
+                }
+                Ok(())
+            }
+            RispError::Exit(code) => write!(f, "exit with status {}", code),
+        }
+    }
+}
+
+impl error::Error for RispError {}
+
+#[derive(Debug, PartialEq)]
+pub enum Token {
+    LParen,
+    RParen,
+    Quote,
+    Quasiquote,
+    Unquote,
+    UnquoteSplicing,
+    Number(i64),
+    BigInt(BigInt),
+    Float(f64),
+    Nil,
+    Bool(bool),
+    Char(char),
+    Str(String),
+    Word(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Node {
+    Null,
+    // shared so that a form keeps its identity, and with it its span, when cloned
+    List(Rc<Vec<Node>>),
+    // an improper cons cell, made by consing onto something that is not a list,
+    // shared so that consing onto a long chain does not copy it
+    Pair(Rc<(Node, Node)>),
+    // shared, so that copying a dict into a new one does not copy its values
+    Dict(Rc<BTreeMap<Key, Node>>),
+    // shared rather than copied, so vector-set! is seen through every reference
+    Vector(Rc<RefCell<Vec<Node>>>),
+    Number(i64),
+    // only ever holds integers outside the range of an i64, smaller ones are Numbers
+    BigInt(BigInt),
+    // never has a denominator of 1, whole results are integers
+    Ratio(Ratio),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    Word(Symbol),
+    Closure(Rc<Closure>),
+    // a builtin procedure such as + used as a value, named by its symbol
+    Builtin(Symbol),
+}
+
+thread_local! {
+    static SYMBOLS: RefCell<HashSet<Rc<str>>> = RefCell::new(HashSet::new());
+    static BUILTIN_NAMES: HashSet<Symbol> = BUILTINS.iter().map(|name| Symbol::new(name)).collect();
+    // where each list read from source starts, keyed by its address
+    static SPANS: RefCell<HashMap<*const Vec<Node>, SpanEntry>> = RefCell::new(HashMap::new());
+}
+
+/// An interned name. Every symbol with the same name shares one allocation,
+/// so comparing and hashing symbols never looks at their text.
+#[derive(Clone)]
+pub struct Symbol(Rc<str>);
+
+impl Symbol {
+    pub fn new(name: &str) -> Symbol {
+        SYMBOLS.with(|symbols| {
+            let mut symbols = symbols.borrow_mut();
+            match symbols.get(name) {
+                Some(interned) => Symbol(interned.clone()),
+                None => {
+                    let interned: Rc<str> = Rc::from(name);
+                    symbols.insert(interned.clone());
+                    Symbol(interned)
+                }
+            }
+        })
+    }
+
+    // a symbol that is not interned, so it equals no other symbol, even one
+    // with the same name
+    fn uninterned(name: &str) -> Symbol {
+        Symbol(Rc::from(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Symbol) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_ptr().hash(state);
+    }
+}
+
+// ordered by name rather than address, so dicts print the same on every run,
+// with the address only telling apart uninterned symbols of the same name
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Symbol) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Symbol) -> std::cmp::Ordering {
+        self.as_str()
+            .cmp(other.as_str())
+            .then_with(|| self.0.as_ptr().cmp(&other.0.as_ptr()))
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The values that can be used as dict keys.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Key {
+    Number(i64),
+    Bool(bool),
+    Str(String),
+    Word(Symbol),
+}
+
+impl Key {
+    fn from_node(node: &Node) -> Option<Key> {
+        match node {
+            Node::Number(n) => Some(Key::Number(*n)),
+            Node::Bool(b) => Some(Key::Bool(*b)),
+            Node::Str(s) => Some(Key::Str(s.clone())),
+            Node::Word(w) => Some(Key::Word(w.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Number(n) => write!(f, "{}", n),
+            Key::Bool(b) => write!(f, "{}", b),
+            Key::Str(s) => write!(f, "{}", s),
+            Key::Word(w) => write!(f, "{}", w),
+        }
+    }
+}
+
+pub struct Closure {
+    params: Vec<Symbol>,
+    body: Vec<Node>,
+    env: Env,
+}
+
+// closures are only equal to themselves
+impl PartialEq for Closure {
+    fn eq(&self, other: &Closure) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+// the captured environment is left out, it may contain the closure itself
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Closure")
+            .field("params", &self.params)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Node::Null => write!(f, "nil"),
+            Node::Number(n) => write!(f, "{}", n),
+            Node::BigInt(n) => write!(f, "{}", n),
+            Node::Ratio(n) => write!(f, "{}", n),
+            // Debug keeps the decimal point on whole floats like 1.0
+            Node::Float(n) => write!(f, "{:?}", n),
+            Node::Bool(b) => write!(f, "{}", b),
+            Node::Char(c) => match char_name(*c) {
+                Some(name) => write!(f, "#\\{}", name),
+                None => write!(f, "#\\{}", c),
+            },
+            Node::Str(s) => write!(f, "{}", s),
+            Node::Word(s) => write!(f, "{}", s),
+            Node::Closure(c) => {
+                let params: Vec<&str> = c.params.iter().map(Symbol::as_str).collect();
+                write!(f, "<lambda ({})>", params.join(" "))
+            }
+            Node::Builtin(name) => write!(f, "<builtin {}>", name),
+            Node::List(_) if f.alternate() => self.fmt_pretty(f, 0),
+            Node::List(_) | Node::Pair(_) | Node::Vector(_) | Node::Dict(_) => {
+                let mut open = Vec::new();
+                write_item(self, f, &mut open)?;
+                write_nested(f, open)
+            }
+        }
+    }
+}
+
+// dropping the last reference to a nested value would otherwise drop its items
+// by recursion, one level per level of nesting, so they are moved out onto a
+// stack first and dropped from there
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        take_items(self, &mut pending);
+        while let Some(mut node) = pending.pop() {
+            take_items(&mut node, &mut pending);
+        }
+    }
+}
+
+// moves the items out of a value that nothing else refers to
+fn take_items(node: &mut Node, pending: &mut Vec<Node>) {
+    match node {
+        Node::List(items) => {
+            if let Some(items) = Rc::get_mut(items) {
+                pending.append(items);
+            }
+        }
+        Node::Vector(items) => {
+            if let Some(items) = Rc::get_mut(items) {
+                pending.append(items.get_mut());
+            }
+        }
+        Node::Pair(cell) => {
+            if let Some((car, cdr)) = Rc::get_mut(cell) {
+                pending.push(std::mem::replace(car, Node::Null));
+                pending.push(std::mem::replace(cdr, Node::Null));
+            }
+        }
+        Node::Dict(dict) => {
+            if let Some(dict) = Rc::get_mut(dict) {
+                pending.extend(std::mem::take(dict).into_values());
+            }
+        }
+        _ => {}
+    }
+}
+
+// compared with a stack of pairs still to compare rather than by recursion,
+// which deeply nested data built at runtime would overflow
+impl PartialEq for Node {
+    fn eq(&self, other: &Node) -> bool {
+        let mut pending = Vec::new();
+        let mut compared = HashSet::new();
+        if !shallow_eq(self, other, &mut pending, &mut compared) {
+            return false;
+        }
+        while let Some((left, right)) = pending.pop() {
+            if !shallow_eq(&left, &right, &mut pending, &mut compared) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// the addresses of two vectors whose items are already being compared
+type VectorPair = (*const RefCell<Vec<Node>>, *const RefCell<Vec<Node>>);
+
+// compares atoms, and the shape of nested values, whose items are added to
+// pending to be compared in turn. A pair of vectors met again, as in vectors
+// that contain themselves, is equal unless their items turn out not to be
+fn shallow_eq(
+    left: &Node,
+    right: &Node,
+    pending: &mut Vec<(Node, Node)>,
+    compared: &mut HashSet<VectorPair>,
+) -> bool {
+    match (left, right) {
+        (Node::List(l), Node::List(r)) => {
+            Rc::ptr_eq(l, r)
+                || l.len() == r.len() && {
+                    pending.extend(l.iter().cloned().zip(r.iter().cloned()));
+                    true
+                }
+        }
+        (Node::Vector(l), Node::Vector(r)) => {
+            if !compared.insert((Rc::as_ptr(l), Rc::as_ptr(r))) {
+                return true;
+            }
+            let (l, r) = (l.borrow(), r.borrow());
+            l.len() == r.len() && {
+                pending.extend(l.iter().cloned().zip(r.iter().cloned()));
+                true
+            }
+        }
+        (Node::Pair(l), Node::Pair(r)) => {
+            Rc::ptr_eq(l, r) || {
+                pending.push((l.0.clone(), r.0.clone()));
+                pending.push((l.1.clone(), r.1.clone()));
+                true
+            }
+        }
+        (Node::Dict(l), Node::Dict(r)) => {
+            l.len() == r.len() && l.keys().eq(r.keys()) && {
+                pending.extend(l.values().cloned().zip(r.values().cloned()));
+                true
+            }
+        }
+        (Node::Null, Node::Null) => true,
+        (Node::Number(l), Node::Number(r)) => l == r,
+        (Node::BigInt(l), Node::BigInt(r)) => l == r,
+        (Node::Ratio(l), Node::Ratio(r)) => l == r,
+        (Node::Float(l), Node::Float(r)) => l == r,
+        (Node::Bool(l), Node::Bool(r)) => l == r,
+        (Node::Char(l), Node::Char(r)) => l == r,
+        (Node::Str(l), Node::Str(r)) => l == r,
+        (Node::Word(l), Node::Word(r)) => l == r,
+        (Node::Closure(l), Node::Closure(r)) => l == r,
+        (Node::Builtin(l), Node::Builtin(r)) => l == r,
+        _ => false,
+    }
+}
+
+// a list, vector, pair or dict partly written, with where it has got to
+enum Written {
+    List(Rc<Vec<Node>>, usize),
+    Vector(Rc<RefCell<Vec<Node>>>, usize),
+    // the rest of the chain, and whether its first element is still to come
+    Pair(Option<Node>, bool),
+    Dict(Vec<(Key, Node)>, usize),
+}
+
+// writes an atom, or the start of a nested value, which is added to open so
+// that its items are written in turn
+fn write_item(node: &Node, f: &mut fmt::Formatter, open: &mut Vec<Written>) -> fmt::Result {
+    match node {
+        Node::List(items) => {
+            open.push(Written::List(items.clone(), 0));
+            write!(f, "(")
+        }
+        // a vector that contains itself is cut short where it comes back around
+        Node::Vector(items)
+            if open.iter().any(
+                |written| matches!(written, Written::Vector(outer, _) if Rc::ptr_eq(outer, items)),
+            ) =>
+        {
+            write!(f, "#(...)")
+        }
+        Node::Vector(items) => {
+            open.push(Written::Vector(items.clone(), 0));
+            write!(f, "#(")
+        }
+        Node::Pair(_) => {
+            open.push(Written::Pair(Some(node.clone()), true));
+            write!(f, "(")
+        }
+        Node::Dict(dict) => {
+            let entries = dict.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+            open.push(Written::Dict(entries, 0));
+            write!(f, "{{")
+        }
+        atom => write!(f, "{}", atom),
+    }
+}
+
+// nested values are written with a stack of their own rather than by
+// recursion, which deeply nested data built at runtime would overflow
+fn write_nested(f: &mut fmt::Formatter, mut open: Vec<Written>) -> fmt::Result {
+    while let Some(written) = open.last_mut() {
+        let next = match written {
+            Written::List(items, i) => next_item(f, items.get(*i).cloned(), i)?,
+            Written::Vector(items, i) => {
+                let item = items.borrow().get(*i).cloned();
+                next_item(f, item, i)?
+            }
+            Written::Dict(entries, i) => match entries.get(*i) {
+                Some((key, value)) => {
+                    let value = value.clone();
+                    if *i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} ", key)?;
+                    *i += 1;
+                    Some(value)
+                }
+                None => None,
+            },
+            Written::Pair(rest, first) => match rest.take() {
+                Some(Node::Pair(ref cell)) => {
+                    if !*first {
+                        write!(f, " ")?;
+                    }
+                    *first = false;
+                    *rest = Some(cell.1.clone());
+                    Some(cell.0.clone())
+                }
+                Some(tail) => {
+                    write!(f, " . ")?;
+                    Some(tail)
+                }
+                None => None,
+            },
+        };
+        match next {
+            Some(item) => write_item(&item, f, &mut open)?,
+            None => {
+                let close = match open.pop() {
+                    Some(Written::Dict(..)) => "}",
+                    _ => ")",
+                };
+                write!(f, "{}", close)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// steps past the item at i of a list or vector, writing the separator before it
+fn next_item(
+    f: &mut fmt::Formatter,
+    item: Option<Node>,
+    i: &mut usize,
+) -> Result<Option<Node>, fmt::Error> {
+    if item.is_some() {
+        if *i > 0 {
+            write!(f, " ")?;
+        }
+        *i += 1;
+    }
+    Ok(item)
+}
+
+impl Node {
+    // {:#} puts every element of a list holding other lists on its own line,
+    // indented under the head, and keeps flat lists on one line
+    fn fmt_pretty(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        let list = match self {
+            Node::List(list)
+                if list
+                    .iter()
+                    .any(|node| matches!(node, Node::List(l) if !l.is_empty())) =>
+            {
+                list
+            }
+            _ => return write!(f, "{}", self),
+        };
+        write!(f, "(")?;
+        for (i, obj) in list.iter().enumerate() {
+            if i == 0 {
+                obj.fmt_pretty(f, indent + 1)?;
+            } else {
+                write!(f, "\n{:width$}", "", width = indent + 2)?;
+                obj.fmt_pretty(f, indent + 2)?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+#[derive(Default)]
+struct Scope {
+    vars: HashMap<Symbol, Node>,
+    // macros live apart from vars, so a macro never shows up as a value
+    macros: HashMap<Symbol, Rc<Closure>>,
+    parent: Option<Env>,
+}
+
+/// How deeply forms may nest during evaluation unless `Env::set_max_depth`
+/// says otherwise. A level can take close to 10 KiB of Rust stack in a debug
+/// build, so this fits with room to spare in the 8 MiB stack of an ordinary
+/// main thread. Callers that run the interpreter on a bigger stack can raise
+/// the limit to match, and those on a smaller one, such as the 2 MiB of a
+/// spawned thread, must lower it. Values built while running may nest any
+/// depth, since they are compared, printed and dropped without recursion.
+pub const DEFAULT_MAX_DEPTH: usize = 500;
+
+// interpreter state shared by every scope descending from the same root
+struct State {
+    depth: Cell<usize>,
+    max_depth: Cell<usize>,
+    // the file being loaded, which relative load paths are resolved against
+    file: RefCell<Option<PathBuf>>,
+    // the number of symbols handed out by gensym so far
+    gensyms: Cell<u64>,
+    // whether each list form and its value are logged as they are evaluated
+    trace: Cell<bool>,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            depth: Cell::new(0),
+            max_depth: Cell::new(DEFAULT_MAX_DEPTH),
+            file: RefCell::new(None),
+            gensyms: Cell::new(0),
+            trace: Cell::new(false),
+        }
+    }
+}
+
+// a handle to a scope, cloning it shares the bindings rather than copying them
+#[derive(Clone, Default)]
+pub struct Env {
+    scope: Rc<RefCell<Scope>>,
+    state: Rc<State>,
+}
+
+impl Env {
+    pub fn new() -> Env {
+        Env::default()
+    }
+
+    /// Limits how deeply forms may nest during evaluation, including calls.
+    pub fn set_max_depth(&self, max_depth: usize) {
+        self.state.max_depth.set(max_depth);
+    }
+
+    /// Logs every list form to stderr before it is evaluated and its value
+    /// after, indented by how deeply it is nested.
+    pub fn set_trace(&self, trace: bool) {
+        self.state.trace.set(trace);
+    }
+
+    fn child(&self) -> Env {
+        Env {
+            scope: Rc::new(RefCell::new(Scope {
+                vars: HashMap::new(),
+                macros: HashMap::new(),
+                parent: Some(self.clone()),
+            })),
+            state: self.state.clone(),
+        }
+    }
+
+    fn get(&self, name: &Symbol) -> Option<Node> {
+        let scope = self.scope.borrow();
+        match scope.vars.get(name) {
+            Some(value) => Some(value.clone()),
+            None => scope.parent.as_ref().and_then(|parent| parent.get(name)),
+        }
+    }
+
+    fn set(&self, name: &Symbol, value: Node) {
+        self.scope.borrow_mut().vars.insert(name.clone(), value);
+    }
+
+    fn get_macro(&self, name: &Symbol) -> Option<Rc<Closure>> {
+        let scope = self.scope.borrow();
+        match scope.macros.get(name) {
+            Some(expander) => Some(expander.clone()),
+            None => scope
+                .parent
+                .as_ref()
+                .and_then(|parent| parent.get_macro(name)),
+        }
+    }
+
+    fn set_macro(&self, name: &Symbol, expander: Rc<Closure>) {
+        self.scope
+            .borrow_mut()
+            .macros
+            .insert(name.clone(), expander);
+    }
+
+    // every binding visible from this scope, where an inner binding hides an
+    // outer one of the same name
+    fn bindings(&self) -> BTreeMap<Key, Node> {
+        let scope = self.scope.borrow();
+        let mut bindings = match &scope.parent {
+            Some(parent) => parent.bindings(),
+            None => BTreeMap::new(),
+        };
+        for (name, value) in &scope.vars {
+            bindings.insert(Key::Word(name.clone()), value.clone());
+        }
+        bindings
+    }
+
+    // rebinds name in the nearest scope that has it, false if none does
+    fn update(&self, name: &Symbol, value: Node) -> bool {
+        let mut scope = self.scope.borrow_mut();
+        if let Some(slot) = scope.vars.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+        match &scope.parent {
+            Some(parent) => parent.update(name, value),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+// walks the source one character at a time while keeping track of where it is
+struct Cursor<I: Iterator<Item = char>> {
+    chars: Peekable<I>,
+    position: Position,
+}
+
+impl<I: Iterator<Item = char>> Cursor<I> {
+    fn next_char(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        if ch == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(ch)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+}
+
+/// Where a form read from source starts, and the text of the line it starts on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub position: Position,
+    pub line: String,
+}
+
+// the weak reference keeps the address of the list from being reused by
+// another one for as long as the entry is there
+struct SpanEntry {
+    list: Weak<Vec<Node>>,
+    position: Position,
+    source: Rc<str>,
+}
+
+fn record_span(list: &Rc<Vec<Node>>, position: Position, source: &Rc<str>) {
+    SPANS.with(|spans| {
+        spans.borrow_mut().insert(
+            Rc::as_ptr(list),
+            SpanEntry {
+                list: Rc::downgrade(list),
+                position,
+                source: source.clone(),
+            },
+        )
+    });
+}
+
+fn span_of(list: &Rc<Vec<Node>>) -> Option<Span> {
+    SPANS.with(|spans| {
+        let spans = spans.borrow();
+        let entry = spans.get(&Rc::as_ptr(list))?;
+        Some(Span {
+            position: entry.position,
+            line: entry
+                .source
+                .lines()
+                .nth(entry.position.line - 1)
+                .unwrap_or("")
+                .to_string(),
+        })
+    })
+}
+
+// forgets the lists that are gone, so the table only grows with live code
+fn prune_spans() {
+    SPANS.with(|spans| {
+        spans
+            .borrow_mut()
+            .retain(|_, entry| entry.list.strong_count() > 0)
+    });
+}
+
+const FILE_START: Position = Position { line: 1, column: 1 };
+
+// the characters that end a number, word or character name, so that a comment
+// or a string may follow one directly
+fn is_delimiter(ch: char) -> bool {
+    ch.is_whitespace() || matches!(ch, '(' | ')' | ';' | '"')
+}
+
+pub fn lex(chars: impl Iterator<Item = char>) -> Result<Vec<(Token, Position)>, RispError> {
+    let mut tokens: Vec<(Token, Position)> = Vec::new();
+
+    let mut cursor = Cursor {
+        chars: chars.peekable(),
+        position: FILE_START,
+    };
+
+    loop {
+        let start = cursor.position;
+        let ch = match cursor.next_char() {
+            Some(ch) => ch,
+            None => break,
+        };
+        match ch {
+            '(' => tokens.push((Token::LParen, start)),
+            ')' => tokens.push((Token::RParen, start)),
+            '\'' => tokens.push((Token::Quote, start)),
+            '`' => tokens.push((Token::Quasiquote, start)),
+            ',' if cursor.peek() == Some('@') => {
+                cursor.next_char();
+                tokens.push((Token::UnquoteSplicing, start));
+            }
+            ',' => tokens.push((Token::Unquote, start)),
+            ';' => skip_line(&mut cursor),
+            // a #! line opening the file is a shebang, so scripts can be run directly
+            '#' if start == FILE_START && cursor.peek() == Some('!') => skip_line(&mut cursor),
+            '#' if cursor.peek() == Some('\\') => {
+                cursor.next_char();
+                tokens.push((Token::Char(lex_char(&mut cursor, start)?), start));
+            }
+            '#' if cursor.peek() == Some('|') => {
+                cursor.next_char();
+                skip_block_comment(&mut cursor, start)?;
+            }
+            '"' => tokens.push((Token::Str(lex_string(&mut cursor, start)?), start)),
+            _ if ch.is_whitespace() => {}
+            _ => {
+                let mut word = ch.to_string();
+                // a `-` directly followed by a digit is a sign, otherwise it is a
+                // word. Only a digit or a sign starts a number, so _5 is a symbol,
+                // like _x, and not a number with a misplaced separator
+                let negative = ch == '-' && cursor.peek().is_some_and(|next| next.is_numeric());
+                if ch.is_numeric() || negative {
+                    while let Some(next) = cursor.peek() {
+                        if is_delimiter(next) {
+                            break;
+                        }
+                        word.push(next);
+                        cursor.next_char();
+                    }
+                    match parse_number(&word) {
+                        Some(token) => tokens.push((token, start)),
+                        None => {
+                            return Err(RispError::LexError(format!(
+                                "invalid number literal {} at {}",
+                                word, start
+                            )))
+                        }
+                    }
+                } else {
+                    while let Some(next) = cursor.peek() {
+                        if is_delimiter(next) {
+                            break;
+                        }
+                        word.push(next);
+                        cursor.next_char();
+                    }
+                    let token = match word.as_str() {
+                        "#t" | "true" => Token::Bool(true),
+                        "#f" | "false" => Token::Bool(false),
+                        "nil" => Token::Nil,
+                        _ => Token::Word(word),
+                    };
+                    tokens.push((token, start));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// decimal integers and floats, plus 0x, 0o and 0b prefixed integers, any of
+// which may use single underscores between digits as separators. Integers past
+// the range of an i64 are bignums, whatever their radix
+fn parse_number(word: &str) -> Option<Token> {
+    let (sign, digits) = match word.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", word),
+    };
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, digits),
+    };
+    let misplaced_separator = digits.starts_with('_')
+        || digits.ends_with('_')
+        || digits.contains("__")
+        || digits.contains("_.")
+        || digits.contains("._")
+        || (radix == 10 && ["_e", "e_", "_E", "E_"].iter().any(|s| digits.contains(s)));
+    // from_str_radix would accept a second sign after the prefix
+    if misplaced_separator || digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let number = format!("{}{}", sign, digits.replace('_', ""));
+    if radix != 10 {
+        match i64::from_str_radix(&number, radix) {
+            Ok(n) => Some(Token::Number(n)),
+            Err(_) => BigInt::parse_radix(&number, radix).map(Token::BigInt),
+        }
+    } else if number.contains(['.', 'e', 'E']) {
+        number.parse::<f64>().ok().map(Token::Float)
+    } else {
+        match number.parse::<i64>() {
+            Ok(n) => Some(Token::Number(n)),
+            Err(_) => BigInt::parse(&number).map(Token::BigInt),
+        }
+    }
+}
+
+// stops in front of the line break, which is left to end the line like any
+// other. A \r\n break is whitespace followed by the newline that counts
+fn skip_line(cursor: &mut Cursor<impl Iterator<Item = char>>) {
+    while cursor.peek().is_some_and(|ch| ch != '\n' && ch != '\r') {
+        cursor.next_char();
+    }
+}
+
+// the characters that are written by name, as in #\space
+const CHAR_NAMES: [(&str, char); 3] = [("space", ' '), ("newline", '\n'), ("tab", '\t')];
+
+fn char_name(c: char) -> Option<&'static str> {
+    CHAR_NAMES
+        .iter()
+        .find(|(_, named)| *named == c)
+        .map(|(name, _)| *name)
+}
+
+// reads what follows #\, a single character, even a delimiter like ( or a
+// space, or the name of one
+fn lex_char(
+    cursor: &mut Cursor<impl Iterator<Item = char>>,
+    start: Position,
+) -> Result<char, RispError> {
+    let first = match cursor.next_char() {
+        Some(first) => first,
+        None => {
+            return Err(RispError::LexError(format!(
+                "missing character after #\\ at {}",
+                start
+            )))
+        }
+    };
+    let mut name = first.to_string();
+    while let Some(next) = cursor.peek() {
+        if is_delimiter(next) {
+            break;
+        }
+        name.push(next);
+        cursor.next_char();
+    }
+    if name.chars().count() == 1 {
+        return Ok(first);
+    }
+    match CHAR_NAMES.iter().find(|(known, _)| *known == name) {
+        Some((_, c)) => Ok(*c),
+        None => Err(RispError::LexError(format!(
+            "unknown character #\\{} at {}",
+            name, start
+        ))),
+    }
+}
+
+// block comments nest, so #| a #| b |# c |# is a single comment
+fn skip_block_comment(
+    cursor: &mut Cursor<impl Iterator<Item = char>>,
+    start: Position,
+) -> Result<(), RispError> {
+    let mut depth = 1;
+    while let Some(ch) = cursor.next_char() {
+        match (ch, cursor.peek()) {
+            ('#', Some('|')) => {
+                cursor.next_char();
+                depth += 1;
+            }
+            ('|', Some('#')) => {
+                cursor.next_char();
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(RispError::LexError(format!(
+        "unterminated block comment starting at {}",
+        start
+    )))
+}
+
+fn lex_string(
+    cursor: &mut Cursor<impl Iterator<Item = char>>,
+    start: Position,
+) -> Result<String, RispError> {
+    let mut string = String::new();
+    loop {
+        let escape = cursor.position;
+        match cursor.next_char() {
+            Some('"') => return Ok(string),
+            Some('\\') => match cursor.next_char() {
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some('"') => string.push('"'),
+                Some('\\') => string.push('\\'),
+                Some(other) => {
+                    return Err(RispError::LexError(format!(
+                        "unknown escape sequence \\{} at {}",
+                        other, escape
+                    )))
+                }
+                None => break,
+            },
+            Some(ch) => string.push(ch),
+            None => break,
+        }
+    }
+    Err(RispError::LexError(format!(
+        "unterminated string starting at {}",
+        start
+    )))
+}
+
+/// Parses tokens into a program, failing if forms nest more than `max_depth`
+/// deep, so that untrusted input cannot overflow the stack of the parser.
+pub fn parse(tokens: Vec<(Token, Position)>, max_depth: usize) -> Result<Node, RispError> {
+    parse_source(tokens, &Rc::from(""), max_depth)
+}
+
+// like parse, and records which line of source each list starts on
+fn parse_source(
+    tokens: Vec<(Token, Position)>,
+    source: &Rc<str>,
+    max_depth: usize,
+) -> Result<Node, RispError> {
+    prune_spans();
+    let mut list: Vec<Node> = Vec::new();
+
+    let mut tokens = tokens.into_iter();
+    while let Some((token, position)) = tokens.next() {
+        match token {
+            Token::RParen => {
+                return Err(RispError::ParseError(format!(
+                    "unexpected ) at {}",
+                    position
+                )))
+            }
+            token => list.push(parse_node(
+                token,
+                position,
+                &mut tokens,
+                source,
+                0,
+                max_depth,
+            )?),
+        }
+    }
+
+    Ok(Node::List(Rc::new(list)))
+}
+
+fn parse_list(
+    tokens: &mut impl Iterator<Item = (Token, Position)>,
+    start: Position,
+    source: &Rc<str>,
+    depth: usize,
+    max_depth: usize,
+) -> Result<Node, RispError> {
+    let mut list: Vec<Node> = Vec::new();
+
+    while let Some((token, position)) = tokens.next() {
+        match token {
+            Token::RParen => {
+                let list = Rc::new(list);
+                record_span(&list, start, source);
+                return Ok(Node::List(list));
+            }
+            token => list.push(parse_node(
+                token,
+                position,
+                tokens,
+                source,
+                depth + 1,
+                max_depth,
+            )?),
+        }
+    }
+
+    Err(RispError::ParseError(format!("unclosed ( at {}", start)))
+}
+
+fn parse_node(
+    token: Token,
+    position: Position,
+    tokens: &mut impl Iterator<Item = (Token, Position)>,
+    source: &Rc<str>,
+    depth: usize,
+    max_depth: usize,
+) -> Result<Node, RispError> {
+    // depth counts the lists and quotes around the node
+    if depth >= max_depth {
+        return Err(RispError::ParseError(format!(
+            "forms nested more than {} deep at {}",
+            max_depth, position
+        )));
+    }
+    Ok(match token {
+        Token::LParen => return parse_list(tokens, position, source, depth, max_depth),
+        Token::RParen => unreachable!(),
+        // 'x reads as (quote x), and likewise `x, ,x and ,@x
+        Token::Quote | Token::Quasiquote | Token::Unquote | Token::UnquoteSplicing => {
+            let name = match token {
+                Token::Quote => "quote",
+                Token::Quasiquote => "quasiquote",
+                Token::Unquote => "unquote",
+                _ => "unquote-splicing",
+            };
+            let quoted = match tokens.next() {
+                Some((Token::RParen, _)) | None => {
+                    return Err(RispError::ParseError(format!(
+                        "nothing to {} at {}",
+                        name, position
+                    )))
+                }
+                Some((token, quoted_position)) => {
+                    parse_node(token, quoted_position, tokens, source, depth + 1, max_depth)?
+                }
+            };
+            let list = Rc::new(vec![Node::Word(Symbol::new(name)), quoted]);
+            record_span(&list, position, source);
+            Node::List(list)
+        }
+        Token::Number(n) => Node::Number(n),
+        Token::BigInt(n) => Node::BigInt(n),
+        Token::Float(n) => Node::Float(n),
+        Token::Nil => Node::Null,
+        Token::Bool(b) => Node::Bool(b),
+        Token::Char(c) => Node::Char(c),
+        Token::Str(s) => Node::Str(s),
+        Token::Word(w) => Node::Word(Symbol::new(&w)),
+    })
+}
+
+/// Lexes and parses `source` into a program without evaluating it, with forms
+/// nested at most `max_depth` deep.
+pub fn read(source: &str, max_depth: usize) -> Result<Node, RispError> {
+    parse_source(lex(source.chars())?, &Rc::from(source), max_depth)
+}
+
+/// Reads the file at `path` and parses it into a program, like `read`.
+pub fn read_file(path: impl AsRef<Path>, max_depth: usize) -> Result<Node, RispError> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(source) => read(&source, max_depth),
+        Err(e) => Err(RispError::RuntimeError(format!(
+            "failed to read {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Evaluates the file at `path` in `env`, returning the value of its last form.
+/// Relative paths given to `load` inside the file are resolved against it.
+pub fn load(path: impl AsRef<Path>, env: &Env) -> Result<Node, RispError> {
+    let path = path.as_ref();
+    let program = read_file(path, env.state.max_depth.get())?;
+    let previous = env.state.file.replace(Some(path.to_path_buf()));
+    let result = eval(&program, env);
+    env.state.file.replace(previous);
+    result
+}
+
+const PRELUDE: &str = include_str!("prelude.risp");
+
+/// Defines the functions of the standard prelude in `env`.
+pub fn load_prelude(env: &Env) -> Result<(), RispError> {
+    eval(&read(PRELUDE, env.state.max_depth.get())?, env)?;
+    Ok(())
+}
+
+/// Lexes, parses and evaluates `source` in a fresh environment with the prelude loaded.
+pub fn eval_str(source: &str) -> Result<Node, RispError> {
+    let env = Env::new();
+    load_prelude(&env)?;
+    eval(&read(source, DEFAULT_MAX_DEPTH)?, &env)
+}
+
+/// Evaluates a parsed program, returning the value of its last form.
+pub fn eval(program: &Node, env: &Env) -> Result<Node, RispError> {
+    // the top level is a sequence of forms sharing one environment
+    match program {
+        Node::List(forms) => interp_body(forms, env),
+        _ => Ok(Node::Null),
+    }
+}
+
+pub fn interp_node(node: &Node, env: &Env) -> Result<Node, RispError> {
+    match node {
+        Node::List(l) => {
+            let depth = env.state.depth.get();
+            if depth >= env.state.max_depth.get() {
+                return Err(RispError::RuntimeError(
+                    "recursion limit exceeded".to_string(),
+                ));
+            }
+            let trace = env.state.trace.get();
+            if trace {
+                eprintln!("{:2$}{}", "", node, depth * 2);
+            }
+            env.state.depth.set(depth + 1);
+            let result = interp_list(l, env).and_then(finish);
+            env.state.depth.set(depth);
+            if let (true, Ok(value)) = (trace, &result) {
+                eprintln!("{:2$}=> {}", "", value, depth * 2);
+            }
+            result.map_err(|e| locate(e, l))
+        }
+        Node::Word(w) => interp_word(w, env),
+        _ => Ok(node.clone()),
+    }
+}
+
+// forms in tail position are handed back to finish instead of being
+// evaluated in place, so tail calls loop rather than grow the Rust stack
+enum Step {
+    Done(Node),
+    Tail(Node, Env),
+}
+
+fn finish(mut step: Step) -> Result<Node, RispError> {
+    loop {
+        step = match step {
+            Step::Done(value) => return Ok(value),
+            Step::Tail(Node::List(ref list), env) => {
+                // a tail form takes the place of the form it came from, so it
+                // is logged at the same depth, and its value is that form's
+                if env.state.trace.get() {
+                    let depth = env.state.depth.get() - 1;
+                    eprintln!("{:2$}{}", "", Node::List(list.clone()), depth * 2);
+                }
+                interp_list(list, &env).map_err(|e| locate(e, list))?
+            }
+            Step::Tail(node, env) => return interp_node(&node, &env),
+        };
+    }
+}
+
+// gives a runtime error the span of the innermost form read from source that
+// raised it, forms made while running such as macro expansions have none
+fn locate(error: RispError, list: &Rc<Vec<Node>>) -> RispError {
+    match error {
+        RispError::RuntimeError(msg) => match span_of(list) {
+            Some(span) => RispError::RuntimeErrorAt(msg, span),
+            None => RispError::RuntimeError(msg),
+        },
+        error => error,
+    }
+}
+
+// evaluates each form in order and returns the value of the last one
+fn interp_body(body: &[Node], env: &Env) -> Result<Node, RispError> {
+    let mut result = Node::Null;
+    for node in body {
+        result = interp_node(node, env)?;
+    }
+    Ok(result)
+}
+
+// like interp_body, but leaves the last form to the caller as a tail call
+fn interp_tail_body(body: &[Node], env: &Env) -> Result<Step, RispError> {
+    match body.split_last() {
+        Some((last, init)) => {
+            for node in init {
+                interp_node(node, env)?;
+            }
+            Ok(Step::Tail(last.clone(), env.clone()))
+        }
+        None => Ok(Step::Done(Node::Null)),
+    }
+}
+
+// the builtins that evaluate all of their arguments, and so can be passed
+// around and called like closures, unlike special forms such as if
+const BUILTINS: &[&str] = &[
+    "+",
+    "-",
+    "*",
+    "/",
+    "abs",
+    "floor",
+    "ceil",
+    "round",
+    "truncate",
+    "zero?",
+    "positive?",
+    "negative?",
+    "even?",
+    "odd?",
+    "gcd",
+    "lcm",
+    "min",
+    "max",
+    "mod",
+    "expt",
+    "quotient",
+    "remainder",
+    "bit-and",
+    "bit-or",
+    "bit-xor",
+    "shl",
+    "shr",
+    "=",
+    "<",
+    ">",
+    "<=",
+    ">=",
+    "string=?",
+    "string<?",
+    "string>?",
+    "string<=?",
+    "string>=?",
+    "char=?",
+    "char<?",
+    "char>?",
+    "char<=?",
+    "char>=?",
+    "not",
+    "list",
+    "car",
+    "cdr",
+    "cons",
+    "length",
+    "nth",
+    "append",
+    "reverse",
+    "take",
+    "drop",
+    "list-tail",
+    "member",
+    "assoc",
+    "display",
+    "print",
+    "println",
+    "read-line",
+    "exit",
+    "error",
+    "raise",
+    "load",
+    "equal?",
+    "eq?",
+    "number?",
+    "list?",
+    "symbol?",
+    "null?",
+    "bool?",
+    "string?",
+    "procedure?",
+    "dict?",
+    "vector?",
+    "char?",
+    "pair?",
+    "proper-list?",
+    "number->string",
+    "string->number",
+    "string->list",
+    "char->number",
+    "number->char",
+    "string-append",
+    "string-length",
+    "substring",
+    "format",
+    "make-dict",
+    "dict-set",
+    "dict-get",
+    "vector",
+    "vector-ref",
+    "vector-set!",
+    "vector-length",
+    "map",
+    "apply",
+    "filter",
+    "sort",
+    "fold",
+    "reduce",
+    "gensym",
+    "eval",
+];
+
+// runs a builtin on arguments that are already evaluated, whether it was
+// named at the head of a form or called as a value
+fn call_builtin(name: &str, args: &[Node], env: &Env) -> Result<Node, RispError> {
+    match name {
+        "+" | "-" | "*" | "/" => interp_binop(name, args),
+        "abs" | "min" | "max" | "mod" | "expt" | "quotient" | "remainder" | "floor" | "ceil"
+        | "round" | "truncate" | "zero?" | "positive?" | "negative?" | "even?" | "odd?" => {
+            interp_numeric(name, args)
+        }
+        "gcd" | "lcm" => interp_gcd(name, args),
+        "bit-and" | "bit-or" | "bit-xor" | "shl" | "shr" => interp_bitwise(name, args),
+        "=" | "<" | ">" | "<=" | ">=" => interp_compare(name, args),
+        "string=?" | "string<?" | "string>?" | "string<=?" | "string>=?" | "char=?" | "char<?"
+        | "char>?" | "char<=?" | "char>=?" => interp_text_compare(name, args),
+        "not" => match args {
+            [value] => Ok(Node::Bool(!is_truthy(value))),
+            _ => Err(RispError::RuntimeError(
+                "not expects a single argument".to_string(),
+            )),
+        },
+        "list" => Ok(Node::List(Rc::new(args.to_vec()))),
+        "car" | "cdr" | "cons" | "length" | "nth" | "append" | "reverse" | "take" | "drop"
+        | "list-tail" | "member" | "assoc" => interp_list_op(name, args),
+        "display" | "print" | "println" => interp_print(name, args),
+        "read-line" => interp_read_line(args),
+        "exit" => match args {
+            [] => Err(RispError::Exit(0)),
+            // a process can only report the low byte of its status
+            [Node::Number(n @ 0..=255)] => Err(RispError::Exit(*n as i32)),
+            [Node::Number(n)] => Err(RispError::RuntimeError(format!(
+                "exit status {} is not between 0 and 255",
+                n
+            ))),
+            _ => Err(RispError::RuntimeError(
+                "exit expects an optional integer status".to_string(),
+            )),
+        },
+        // (error "message" irritants...) raises a runtime error that try can
+        // catch, its message followed by the irritants as display writes them
+        "error" | "raise" => match args {
+            [Node::Str(message), irritants @ ..] => {
+                let mut message = message.clone();
+                for irritant in irritants {
+                    message.push_str(&format!(" {}", irritant));
+                }
+                Err(RispError::RuntimeError(message))
+            }
+            _ => Err(RispError::RuntimeError(format!(
+                "{} expects a message string and optional irritants",
+                name
+            ))),
+        },
+        "load" => interp_load(args, env),
+        "equal?" | "eq?" => interp_equality(name, args),
+        "number?" | "list?" | "symbol?" | "null?" | "bool?" | "string?" | "procedure?"
+        | "dict?" | "vector?" | "char?" | "pair?" | "proper-list?" => {
+            interp_type_predicate(name, args)
+        }
+        "number->string" | "string->number" | "string->list" | "char->number" | "number->char" => {
+            interp_conversion(name, args)
+        }
+        "string-append" | "string-length" | "substring" => interp_string_op(name, args),
+        "format" => interp_format(args),
+        "make-dict" | "dict-set" | "dict-get" => interp_dict_op(name, args),
+        "vector" | "vector-ref" | "vector-set!" | "vector-length" => interp_vector_op(name, args),
+        "map" => interp_map(args, env),
+        "filter" => interp_filter(args, env),
+        "fold" | "reduce" => interp_fold(args, env),
+        "sort" => interp_sort(args, env),
+        "apply" => interp_apply(args, env),
+        "gensym" => interp_gensym(args, env),
+        // eval runs the code in the current environment, not a fresh one
+        "eval" => match args {
+            [code] => interp_node(code, env),
+            _ => Err(RispError::RuntimeError(
+                "eval expects a single argument".to_string(),
+            )),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn interp_list(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    // the empty list is data and evaluates to itself
+    let head = match list.first() {
+        Some(head) => head,
+        None => return Ok(Step::Done(Node::List(Rc::new(Vec::new())))),
+    };
+    let value = match head {
+        Node::Word(w) => match w.as_str() {
+            "if" => return interp_if(list, env),
+            "cond" => return interp_cond(list, env),
+            "case" => return interp_case(list, env),
+            "when" | "unless" => return interp_when(list, env),
+            "try" => return interp_try(list, env),
+            "begin" => return interp_tail_body(&list[1..], env),
+            "while" => interp_while(list, env),
+            "dotimes" => interp_dotimes(list, env),
+            "and" | "or" => return interp_logic(list, env),
+            "define" => interp_define(list, env),
+            "set!" => interp_set(list, env),
+            "assert" | "assert-eq" => interp_assert(list, env),
+            "lambda" => interp_lambda(list, env),
+            // a dict of every name in scope where it is called, outer scopes
+            // included, so it is not a builtin that can be passed around
+            "env" => match list {
+                [_] => Ok(Node::Dict(Rc::new(env.bindings()))),
+                _ => Err(RispError::RuntimeError(
+                    "env expects no arguments".to_string(),
+                )),
+            },
+            "quote" => match list {
+                [_, quoted] => Ok(quoted.clone()),
+                _ => Err(RispError::RuntimeError(
+                    "quote expects a single argument".to_string(),
+                )),
+            },
+            "quasiquote" => match list {
+                [_, template] => quasiquote(template, env),
+                _ => Err(RispError::RuntimeError(
+                    "quasiquote expects a single argument".to_string(),
+                )),
+            },
+            "unquote" | "unquote-splicing" => Err(RispError::RuntimeError(format!(
+                "{} used outside of quasiquote",
+                w
+            ))),
+            "let" | "let*" | "letrec" => return interp_let(list, env),
+            "defmacro" => interp_defmacro(list, env),
+            // macros take precedence over variables, and a variable over the
+            // builtin of the same name, which interp_word falls back to
+            _ => match env.get_macro(w) {
+                Some(expander) => {
+                    let expansion = apply(&expander, list[1..].to_vec())?;
+                    return Ok(Step::Tail(expansion, env.clone()));
+                }
+                None => return interp_call(list, env),
+            },
+        },
+        _ => return interp_call(list, env),
+    };
+    value.map(Step::Done)
+}
+
+// the head is evaluated once, a closure is applied and anything else is data
+fn interp_call(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    let head = interp_node(&list[0], env)?;
+    match &head {
+        Node::Closure(closure) => {
+            let local = bind_args(closure, eval_args(list, env)?)?;
+            return interp_tail_body(&closure.body, &local);
+        }
+        Node::Builtin(name) => {
+            return call_builtin(name.as_str(), &eval_args(list, env)?, env).map(Step::Done)
+        }
+        _ => {}
+    }
+    // every element is kept, nil included, as list does
+    let mut new_list = vec![head];
+    for node in &list[1..] {
+        new_list.push(interp_node(node, env)?);
+    }
+    Ok(Step::Done(Node::List(Rc::new(new_list))))
+}
+
+// the arguments of a numeric operator, which must all be numbers
+fn check_numbers(op: &str, args: &[Node]) -> Result<(), RispError> {
+    for arg in args {
+        match arg {
+            Node::Number(_) | Node::BigInt(_) | Node::Ratio(_) | Node::Float(_) => {}
+            other => {
+                return Err(RispError::RuntimeError(format!(
+                    "{} expects numbers, got {}",
+                    op, other
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn interp_binop(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    check_numbers(op, args)?;
+    // (+) and (*) give their identities, (- x) negates and (/ x) is 1 / x
+    let (init, rest) = match args {
+        [] => {
+            return match op {
+                "+" => Ok(Node::Number(0)),
+                "*" => Ok(Node::Number(1)),
+                _ => Err(RispError::RuntimeError(format!(
+                    "{} expects at least one argument",
+                    op
+                ))),
+            }
+        }
+        [_] if op == "-" => (Node::Number(0), args),
+        [_] if op == "/" => (Node::Number(1), args),
+        [first, rest @ ..] => (first.clone(), rest),
+    };
+    let mut acc = init;
+    for n in rest {
+        acc = arith(op, &acc, n)?;
+    }
+    Ok(acc)
+}
+
+// integers stay integers, growing into bignums rather than overflowing,
+// dividing them gives an exact ratio unless the division comes out whole,
+// and anything involving a float is promoted to a float
+fn arith(op: &str, left: &Node, right: &Node) -> Result<Node, RispError> {
+    if let (Node::Number(l), Node::Number(r)) = (left, right) {
+        let result = match op {
+            "+" => l.checked_add(*r),
+            "-" => l.checked_sub(*r),
+            "*" => l.checked_mul(*r),
+            "/" if *r == 0 => return Err(RispError::RuntimeError("division by zero".to_string())),
+            "/" if l.checked_rem(*r) == Some(0) => l.checked_div(*r),
+            "/" => None,
+            _ => unreachable!(),
+        };
+        if let Some(n) = result {
+            return Ok(Node::Number(n));
+        }
+    }
+    if op != "/" {
+        if let (Some(l), Some(r)) = (as_bigint(left), as_bigint(right)) {
+            return Ok(int_node(match op {
+                "+" => l.add(&r),
+                "-" => l.sub(&r),
+                _ => l.mul(&r),
+            }));
+        }
+    }
+    if let (Some(l), Some(r)) = (as_ratio(left), as_ratio(right)) {
+        return Ok(ratio_node(match op {
+            "+" => l.add(&r),
+            "-" => l.sub(&r),
+            "*" => l.mul(&r),
+            "/" => match l.div(&r) {
+                Some(quotient) => quotient,
+                None => return Err(RispError::RuntimeError("division by zero".to_string())),
+            },
+            _ => unreachable!(),
+        }));
+    }
+    let (l, r) = (as_float(left), as_float(right));
+    Ok(Node::Float(match op {
+        "+" => l + r,
+        "-" => l - r,
+        "*" => l * r,
+        "/" if r == 0.0 => return Err(RispError::RuntimeError("division by zero".to_string())),
+        "/" => l / r,
+        _ => unreachable!(),
+    }))
+}
+
+fn as_float(node: &Node) -> f64 {
+    match node {
+        Node::Number(n) => *n as f64,
+        Node::BigInt(n) => n.to_f64(),
+        Node::Ratio(n) => n.to_f64(),
+        Node::Float(f) => *f,
+        _ => unreachable!(),
+    }
+}
+
+fn as_bigint(node: &Node) -> Option<BigInt> {
+    match node {
+        Node::Number(n) => Some(BigInt::from_i64(*n)),
+        Node::BigInt(n) => Some(n.clone()),
+        _ => None,
+    }
+}
+
+// the integer result of bignum arithmetic, back as a Number when it fits
+fn int_node(n: BigInt) -> Node {
+    match n.to_i64() {
+        Some(n) => Node::Number(n),
+        None => Node::BigInt(n),
+    }
+}
+
+// any integer or ratio as a ratio, for exact arithmetic between them
+fn as_ratio(node: &Node) -> Option<Ratio> {
+    match node {
+        Node::Ratio(n) => Some(n.clone()),
+        _ => as_bigint(node).map(Ratio::from_integer),
+    }
+}
+
+fn ratio_node(n: Ratio) -> Node {
+    if n.is_integer() {
+        int_node(n.numerator().clone())
+    } else {
+        Node::Ratio(n)
+    }
+}
+
+// integers and ratios compare exactly, even when they are too large for a float
+fn less_than(left: &Node, right: &Node) -> bool {
+    match (as_ratio(left), as_ratio(right)) {
+        (Some(l), Some(r)) => l < r,
+        _ => as_float(left) < as_float(right),
+    }
+}
+
+fn interp_numeric(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    check_numbers(op, args)?;
+    let is_integer = |n: &Node| matches!(n, Node::Number(_) | Node::BigInt(_));
+    match (op, args) {
+        ("abs", [Node::Number(n)]) => Ok(match n.checked_abs() {
+            Some(n) => Node::Number(n),
+            None => Node::BigInt(BigInt::from_i64(*n).abs()),
+        }),
+        ("abs", [Node::BigInt(n)]) => Ok(Node::BigInt(n.abs())),
+        ("abs", [Node::Ratio(n)]) => Ok(Node::Ratio(n.abs())),
+        ("abs", [Node::Float(n)]) => Ok(Node::Float(n.abs())),
+        // the first of several equal candidates wins, keeping its type
+        ("min" | "max", [first, rest @ ..]) => {
+            let mut best = first;
+            for n in rest {
+                let better = if op == "min" {
+                    less_than(n, best)
+                } else {
+                    less_than(best, n)
+                };
+                if better {
+                    best = n;
+                }
+            }
+            Ok(best.clone())
+        }
+        ("mod" | "quotient" | "remainder", [l, r]) if is_integer(l) && is_integer(r) => {
+            integer_division(op, l, r)
+        }
+        ("mod", [_, r]) if as_float(r) == 0.0 => {
+            Err(RispError::RuntimeError("division by zero".to_string()))
+        }
+        ("mod", [l, r]) => {
+            let (l, r) = (as_float(l), as_float(r));
+            Ok(Node::Float(((l % r) + r) % r))
+        }
+        ("quotient" | "remainder", [_, _]) => {
+            Err(RispError::RuntimeError(format!("{} expects integers", op)))
+        }
+        ("expt", [base, Node::Number(exp)]) if is_integer(base) => {
+            let exp = u32::try_from(*exp).map_err(|_| {
+                RispError::RuntimeError("expt expects a non-negative integer exponent".to_string())
+            })?;
+            if let Node::Number(base) = base {
+                if let Some(n) = base.checked_pow(exp) {
+                    return Ok(Node::Number(n));
+                }
+            }
+            // square and multiply
+            let (mut base, mut exp) = (as_bigint(base).unwrap(), exp);
+            let mut result = BigInt::from_i64(1);
+            while exp > 0 {
+                if exp & 1 == 1 {
+                    result = result.mul(&base);
+                }
+                base = base.mul(&base);
+                exp >>= 1;
+            }
+            Ok(int_node(result))
+        }
+        ("expt", [base, Node::BigInt(_)]) if is_integer(base) => Err(RispError::RuntimeError(
+            "expt exponent too large".to_string(),
+        )),
+        ("expt", [base, exp]) => Ok(Node::Float(as_float(base).powf(as_float(exp)))),
+        // the results are integers, integers are already whole and pass
+        // through, and round takes halfway cases away from zero, so
+        // (round 2.5) is 3 and (round -2.5) is -3
+        ("floor" | "ceil" | "round" | "truncate", [n]) if is_integer(n) => Ok(n.clone()),
+        ("floor" | "ceil" | "round" | "truncate", [Node::Ratio(n)]) => Ok(int_node(match op {
+            "floor" => n.floor(),
+            "ceil" => n.ceil(),
+            "round" => n.round(),
+            _ => n.trunc(),
+        })),
+        ("floor" | "ceil" | "round" | "truncate", [Node::Float(n)]) => {
+            let whole = match op {
+                "floor" => n.floor(),
+                "ceil" => n.ceil(),
+                "round" => n.round(),
+                _ => n.trunc(),
+            };
+            if !whole.is_finite() {
+                return Err(RispError::RuntimeError(format!(
+                    "{} expects a finite number, got {}",
+                    op, n
+                )));
+            }
+            // a whole float prints as its exact digits, however large it is
+            Ok(int_node(BigInt::parse(&format!("{:.0}", whole)).unwrap()))
+        }
+        ("zero?", [n]) => Ok(Node::Bool(as_float(n) == 0.0)),
+        ("positive?", [n]) => Ok(Node::Bool(less_than(&Node::Number(0), n))),
+        ("negative?", [n]) => Ok(Node::Bool(less_than(n, &Node::Number(0)))),
+        // parity is only defined for integers, so even 4.0 is an error
+        ("even?" | "odd?", [Node::Number(n)]) => Ok(Node::Bool((n % 2 == 0) == (op == "even?"))),
+        ("even?" | "odd?", [Node::BigInt(n)]) => {
+            let (_, remainder) = n.div_rem(&BigInt::from_i64(2)).unwrap();
+            Ok(Node::Bool(remainder.is_zero() == (op == "even?")))
+        }
+        ("even?" | "odd?", [n]) => Err(RispError::RuntimeError(format!(
+            "{} expects an integer, got {}",
+            op, n
+        ))),
+        (
+            "abs" | "floor" | "ceil" | "round" | "truncate" | "zero?" | "positive?" | "negative?"
+            | "even?" | "odd?",
+            _,
+        ) => Err(RispError::RuntimeError(format!(
+            "{} expects a single number",
+            op
+        ))),
+        ("min" | "max", _) => Err(RispError::RuntimeError(format!(
+            "{} expects at least one number",
+            op
+        ))),
+        _ => Err(RispError::RuntimeError(format!("{} expects 2 numbers", op))),
+    }
+}
+
+// both fold over any number of integers and are never negative. With no
+// arguments they give their identities, 0 for gcd and 1 for lcm
+fn interp_gcd(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    let is_gcd = op == "gcd";
+    let mut result = BigInt::from_i64(if is_gcd { 0 } else { 1 });
+    check_numbers(op, args)?;
+    for n in args {
+        let n = as_bigint(n).ok_or_else(|| {
+            RispError::RuntimeError(format!("{} expects integers, got {}", op, n))
+        })?;
+        result = if is_gcd {
+            ratio::gcd(&result, &n)
+        } else if n.is_zero() || result.is_zero() {
+            BigInt::from_i64(0)
+        } else {
+            let (quotient, _) = result.mul(&n).div_rem(&ratio::gcd(&result, &n)).unwrap();
+            quotient.abs()
+        };
+    }
+    Ok(int_node(result))
+}
+
+// quotient and remainder truncate toward zero, so the remainder has the sign
+// of the dividend, while mod takes the sign of the divisor, so (mod -7 3) is 2
+fn integer_division(op: &str, left: &Node, right: &Node) -> Result<Node, RispError> {
+    if let (Node::Number(l), Node::Number(r)) = (left, right) {
+        if *r == 0 {
+            return Err(RispError::RuntimeError("division by zero".to_string()));
+        }
+        // only i64::MIN / -1 overflows, and goes on to the bignum path
+        if let Some(q) = l.checked_div(*r) {
+            let m = l % r;
+            return Ok(Node::Number(match op {
+                "quotient" => q,
+                "remainder" => m,
+                _ if m != 0 && (m < 0) != (*r < 0) => m + r,
+                _ => m,
+            }));
+        }
+    }
+    let (l, r) = (as_bigint(left).unwrap(), as_bigint(right).unwrap());
+    let (q, m) = match l.div_rem(&r) {
+        Some(result) => result,
+        None => return Err(RispError::RuntimeError("division by zero".to_string())),
+    };
+    let zero = BigInt::from_i64(0);
+    Ok(int_node(match op {
+        "quotient" => q,
+        "remainder" => m,
+        _ if !m.is_zero() && (m < zero) != (r < zero) => m.add(&r),
+        _ => m,
+    }))
+}
+
+fn interp_bitwise(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    let (l, r) = match args {
+        [Node::Number(l), Node::Number(r)] => (*l, *r),
+        [Node::Number(_) | Node::BigInt(_), Node::Number(_) | Node::BigInt(_)] => {
+            return Err(RispError::RuntimeError(format!(
+                "{} only supports integers that fit in 64 bits",
+                op
+            )))
+        }
+        [_, _] => return Err(RispError::RuntimeError(format!("{} expects integers", op))),
+        _ => {
+            return Err(RispError::RuntimeError(format!(
+                "{} expects 2 arguments, got {}",
+                op,
+                args.len()
+            )))
+        }
+    };
+    let result = match op {
+        "bit-and" => l & r,
+        "bit-or" => l | r,
+        "bit-xor" => l ^ r,
+        _ => {
+            let shift = match u32::try_from(r) {
+                Ok(shift) if shift < i64::BITS => shift,
+                _ => {
+                    return Err(RispError::RuntimeError(format!(
+                        "{} expects a shift between 0 and 63, got {}",
+                        op, r
+                    )))
+                }
+            };
+            if op == "shl" {
+                l << shift
+            } else {
+                l >> shift
+            }
+        }
+    };
+    Ok(Node::Number(result))
+}
+
+fn interp_compare(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    let (left, right) = match args {
+        [left, right] => (left, right),
+        _ => {
+            return Err(RispError::RuntimeError(format!(
+                "{} expects 2 arguments, got {}",
+                op,
+                args.len()
+            )))
+        }
+    };
+    match (op, left, right) {
+        (_, Node::Number(l), Node::Number(r)) => Ok(Node::Bool(compare(op, l, r))),
+        (
+            _,
+            Node::Number(_) | Node::BigInt(_) | Node::Ratio(_),
+            Node::Number(_) | Node::BigInt(_) | Node::Ratio(_),
+        ) => Ok(Node::Bool(compare(
+            op,
+            &as_ratio(left).unwrap(),
+            &as_ratio(right).unwrap(),
+        ))),
+        (
+            _,
+            Node::Number(_) | Node::BigInt(_) | Node::Ratio(_) | Node::Float(_),
+            Node::Number(_) | Node::BigInt(_) | Node::Ratio(_) | Node::Float(_),
+        ) => Ok(Node::Bool(compare(op, &as_float(left), &as_float(right)))),
+        // anything else can only be compared for equality
+        ("=", _, _) => Ok(Node::Bool(left == right)),
+        _ => Err(RispError::RuntimeError(format!(
+            "{} expects numbers, got {} and {}",
+            op, left, right
+        ))),
+    }
+}
+
+// strings compare lexicographically by char and chars by code point. Each
+// family only takes its own type, so a string is never compared with a number
+fn interp_text_compare(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    // string<? compares like <, and so on
+    let relation = op
+        .trim_start_matches("string")
+        .trim_start_matches("char")
+        .trim_end_matches('?');
+    match args {
+        [Node::Str(l), Node::Str(r)] if op.starts_with("string") => {
+            Ok(Node::Bool(compare(relation, l, r)))
+        }
+        [Node::Char(l), Node::Char(r)] if op.starts_with("char") => {
+            Ok(Node::Bool(compare(relation, l, r)))
+        }
+        _ => Err(RispError::RuntimeError(format!(
+            "{} expects 2 {}s",
+            op,
+            if op.starts_with("string") {
+                "string"
+            } else {
+                "char"
+            }
+        ))),
+    }
+}
+
+fn compare<T: PartialOrd>(op: &str, l: &T, r: &T) -> bool {
+    match op {
+        "=" => l == r,
+        "<" => l < r,
+        ">" => l > r,
+        "<=" => l <= r,
+        ">=" => l >= r,
+        _ => unreachable!(),
+    }
+}
+
+// only false and Null are falsey, everything else counts as true
+fn is_truthy(node: &Node) -> bool {
+    !matches!(node, Node::Bool(false) | Node::Null)
+}
+
+fn interp_if(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    if list.len() < 3 {
+        return Err(RispError::RuntimeError(
+            "if expects a condition and a branch".to_string(),
+        ));
+    }
+    if is_truthy(&interp_node(&list[1], env)?) {
+        Ok(Step::Tail(list[2].clone(), env.clone()))
+    } else {
+        match list.get(3) {
+            Some(node) => Ok(Step::Tail(node.clone(), env.clone())),
+            None => Ok(Step::Done(Node::Null)),
+        }
+    }
+}
+
+fn interp_cond(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    for clause in &list[1..] {
+        let clause = match clause {
+            Node::List(clause) if !clause.is_empty() => clause,
+            _ => {
+                return Err(RispError::RuntimeError(format!(
+                    "invalid cond clause {}",
+                    clause
+                )))
+            }
+        };
+        let test = match &clause[0] {
+            Node::Word(w) if w == "else" => Node::Bool(true),
+            test => interp_node(test, env)?,
+        };
+        if is_truthy(&test) {
+            // a clause without a body returns the value of its test
+            if clause.len() == 1 {
+                return Ok(Step::Done(test));
+            }
+            return interp_tail_body(&clause[1..], env);
+        }
+    }
+    Ok(Step::Done(Node::Null))
+}
+
+// (when c body...) runs body only if c holds, (unless c body...) only if it doesn't
+fn interp_when(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    let is_when = matches!(&list[0], Node::Word(w) if w == "when");
+    let condition = match list.get(1) {
+        Some(condition) => interp_node(condition, env)?,
+        None => {
+            return Err(RispError::RuntimeError(format!(
+                "{} expects a condition",
+                list[0]
+            )))
+        }
+    };
+    if is_truthy(&condition) == is_when {
+        interp_tail_body(&list[2..], env)
+    } else {
+        Ok(Step::Done(Node::Null))
+    }
+}
+
+// (try body... (catch e handler...)) evaluates body, and if that raises an
+// error evaluates handler instead, with the error message bound to e. Only the
+// handler is in tail position, and (exit n) is not an error, so it is not caught
+fn interp_try(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    let (body, name, handler) = match list.split_last() {
+        Some((Node::List(clause), [_, body @ ..])) => match clause.as_slice() {
+            [Node::Word(catch), Node::Word(name), handler @ ..] if catch == "catch" => {
+                (body, name, handler)
+            }
+            _ => {
+                return Err(RispError::RuntimeError(
+                    "try expects a body and a (catch name handler...) clause".to_string(),
+                ))
+            }
+        },
+        _ => {
+            return Err(RispError::RuntimeError(
+                "try expects a body and a (catch name handler...) clause".to_string(),
+            ))
+        }
+    };
+    let message = match interp_body(body, env) {
+        Ok(value) => return Ok(Step::Done(value)),
+        Err(error @ RispError::Exit(_)) => return Err(error),
+        Err(
+            RispError::LexError(msg)
+            | RispError::ParseError(msg)
+            | RispError::RuntimeError(msg)
+            | RispError::RuntimeErrorAt(msg, _),
+        ) => msg,
+    };
+    let local = env.child();
+    local.set(name, Node::Str(message));
+    interp_tail_body(handler, &local)
+}
+
+// (case key ((datum...) body...) ... (else body...)) compares the key with
+// the unevaluated datums using equal?
+fn interp_case(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    let key = match list.get(1) {
+        Some(key) => interp_node(key, env)?,
+        None => {
+            return Err(RispError::RuntimeError(
+                "case expects a key and clauses".to_string(),
+            ))
+        }
+    };
+    for clause in &list[2..] {
+        let parts = match clause {
+            Node::List(parts) => parts.as_slice(),
+            _ => &[],
+        };
+        match parts {
+            [Node::Word(w), body @ ..] if w == "else" => return interp_tail_body(body, env),
+            [Node::List(datums), body @ ..] => {
+                if datums.contains(&key) {
+                    return interp_tail_body(body, env);
+                }
+            }
+            _ => {
+                return Err(RispError::RuntimeError(format!(
+                    "invalid case clause {}",
+                    clause
+                )))
+            }
+        }
+    }
+    Ok(Step::Done(Node::Null))
+}
+
+fn interp_while(list: &[Node], env: &Env) -> Result<Node, RispError> {
+    if list.len() < 2 {
+        return Err(RispError::RuntimeError(
+            "while expects a condition".to_string(),
+        ));
+    }
+    while is_truthy(&interp_node(&list[1], env)?) {
+        interp_body(&list[2..], env)?;
+    }
+    Ok(Node::Null)
+}
+
+// (dotimes (i n) body...) runs body with i bound to 0 up to n - 1, in a
+// scope of its own so that i is gone once the loop ends
+fn interp_dotimes(list: &[Node], env: &Env) -> Result<Node, RispError> {
+    let spec = match list.get(1) {
+        Some(Node::List(spec)) => spec.as_slice(),
+        _ => &[],
+    };
+    let (name, count) = match spec {
+        [Node::Word(name), count] => (name, count),
+        _ => {
+            return Err(RispError::RuntimeError(
+                "dotimes expects (name count) and a body".to_string(),
+            ))
+        }
+    };
+    let count = match interp_node(count, env)? {
+        Node::Number(n) => n,
+        other => {
+            return Err(RispError::RuntimeError(format!(
+                "dotimes expects an integer count, got {}",
+                other
+            )))
+        }
+    };
+    let local = env.child();
+    for i in 0..count {
+        local.set(name, Node::Number(i));
+        interp_body(&list[2..], &local)?;
+    }
+    Ok(Node::Null)
+}
+
+// and stops at the first falsey value, or at the first truthy one, and the
+// value that stopped evaluation (or the last one) is returned
+// the last operand is in tail position, since its value is the result as is
+fn interp_logic(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    let is_and = matches!(&list[0], Node::Word(w) if w == "and");
+    let (last, init) = match list[1..].split_last() {
+        Some(operands) => operands,
+        None => return Ok(Step::Done(Node::Bool(is_and))),
+    };
+    for node in init {
+        let result = interp_node(node, env)?;
+        if is_truthy(&result) != is_and {
+            return Ok(Step::Done(result));
+        }
+    }
+    Ok(Step::Tail(last.clone(), env.clone()))
+}
+
+fn interp_define(list: &[Node], env: &Env) -> Result<Node, RispError> {
+    match list {
+        [_, Node::Word(name), value] => {
+            let value = interp_node(value, env)?;
+            env.set(name, value);
+            Ok(Node::Null)
+        }
+        // (define (name params...) body...) is shorthand for a lambda
+        [_, Node::List(signature), body @ ..] if !body.is_empty() => {
+            match signature.split_first() {
+                Some((Node::Word(name), params)) => {
+                    let closure = make_closure(params, body, env)?;
+                    env.set(name, closure);
+                    Ok(Node::Null)
+                }
+                _ => Err(RispError::RuntimeError(format!(
+                    "invalid function signature {}",
+                    Node::List(signature.clone())
+                ))),
+            }
+        }
+        _ => Err(RispError::RuntimeError(
+            "define expects a name and a value".to_string(),
+        )),
+    }
+}
+
+// (defmacro name (params...) body...) binds the unevaluated arguments of a call
+// to params, and evaluates the code that body returns in place of the call
+fn interp_defmacro(list: &[Node], env: &Env) -> Result<Node, RispError> {
+    match list {
+        [_, Node::Word(name), Node::List(params), body @ ..] if !body.is_empty() => {
+            match make_closure(params, body, env)? {
+                Node::Closure(ref expander) => env.set_macro(name, expander.clone()),
+                _ => unreachable!(),
+            }
+            Ok(Node::Null)
+        }
+        _ => Err(RispError::RuntimeError(
+            "defmacro expects a name, a parameter list and a body".to_string(),
+        )),
+    }
+}
+
+fn interp_set(list: &[Node], env: &Env) -> Result<Node, RispError> {
+    match list {
+        [_, Node::Word(name), value] => {
+            let value = interp_node(value, env)?;
+            if env.update(name, value) {
+                Ok(Node::Null)
+            } else {
+                Err(RispError::RuntimeError(format!(
+                    "cannot set! unbound symbol {}",
+                    name
+                )))
+            }
+        }
+        _ => Err(RispError::RuntimeError(
+            "set! expects a name and a value".to_string(),
+        )),
+    }
+}
+
+// the symbols are uninterned, so they cannot be captured by a name in the
+// program, and the counter shared by every env of the run keeps their names
+// apart when printed
+fn interp_gensym(args: &[Node], env: &Env) -> Result<Node, RispError> {
+    let prefix = match args {
+        [] => "__g".to_string(),
+        [Node::Str(prefix)] => prefix.clone(),
+        _ => {
+            return Err(RispError::RuntimeError(
+                "gensym expects an optional prefix string".to_string(),
+            ))
+        }
+    };
+    let count = env.state.gensyms.get() + 1;
+    env.state.gensyms.set(count);
+    Ok(Node::Word(Symbol::uninterned(&format!(
+        "{}{}",
+        prefix, count
+    ))))
+}
+
+fn interp_lambda(list: &[Node], env: &Env) -> Result<Node, RispError> {
+    let (params, body) = match list {
+        [_, Node::List(params), body @ ..] if !body.is_empty() => (params, body),
+        _ => {
+            return Err(RispError::RuntimeError(
+                "lambda expects a parameter list and a body".to_string(),
+            ))
+        }
+    };
+    make_closure(params, body, env)
+}
+
+fn make_closure(params: &[Node], body: &[Node], env: &Env) -> Result<Node, RispError> {
+    let mut names: Vec<Symbol> = Vec::new();
+    for param in params {
+        match param {
+            Node::Word(name) => names.push(name.clone()),
+            other => {
+                return Err(RispError::RuntimeError(format!(
+                    "parameters must be symbols, got {}",
+                    other
+                )))
+            }
+        }
+    }
+    Ok(Node::Closure(Rc::new(Closure {
+        params: names,
+        body: body.to_vec(),
+        env: env.clone(),
+    })))
+}
+
+// copies the template, replacing (unquote x) with the value of x and
+// splicing the elements of the list from (unquote-splicing x) in place
+fn quasiquote(template: &Node, env: &Env) -> Result<Node, RispError> {
+    let list = match template {
+        Node::List(list) => list,
+        _ => return Ok(template.clone()),
+    };
+    if let [Node::Word(w), value] = list.as_slice() {
+        if w == "unquote" {
+            return interp_node(value, env);
+        }
+    }
+    let mut result: Vec<Node> = Vec::new();
+    for node in list.iter() {
+        match spliced_form(node) {
+            Some(value) => match interp_node(value, env)? {
+                Node::List(ref spliced) => result.extend(spliced.iter().cloned()),
+                other => {
+                    return Err(RispError::RuntimeError(format!(
+                        "unquote-splicing expects a list, got {}",
+                        other
+                    )))
+                }
+            },
+            None => result.push(quasiquote(node, env)?),
+        }
+    }
+    Ok(Node::List(Rc::new(result)))
+}
+
+// the x in (unquote-splicing x)
+fn spliced_form(node: &Node) -> Option<&Node> {
+    match node {
+        Node::List(list) => match list.as_slice() {
+            [Node::Word(w), value] if w == "unquote-splicing" => Some(value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn interp_let(list: &[Node], env: &Env) -> Result<Step, RispError> {
+    if let [Node::Word(w), Node::Word(name), Node::List(bindings), body @ ..] = list {
+        if w == "let" && !body.is_empty() {
+            return interp_named_let(name, bindings, body, env);
+        }
+    }
+    let (bindings, body) = match list {
+        [_, Node::List(bindings), body @ ..] if !body.is_empty() => (bindings, body),
+        _ => {
+            return Err(RispError::RuntimeError(format!(
+                "{} expects a binding list and a body",
+                list[0]
+            )))
+        }
+    };
+    let op = match &list[0] {
+        Node::Word(w) => w.as_str(),
+        _ => unreachable!(),
+    };
+    let mut local = env.child();
+    match op {
+        // every value is evaluated in the outer scope before any name is bound
+        "let" => {
+            for binding in bindings.iter() {
+                let (name, value) = let_binding(binding)?;
+                local.set(name, interp_node(value, env)?);
+            }
+        }
+        // each binding gets a scope of its own that the later ones can see
+        "let*" => {
+            for binding in bindings.iter() {
+                let (name, value) = let_binding(binding)?;
+                let value = interp_node(value, &local)?;
+                local = local.child();
+                local.set(name, value);
+            }
+        }
+        // every name is bound, to Null at first, before any value is evaluated,
+        // so lambdas among the values can call each other
+        _ => {
+            let bindings = bindings
+                .iter()
+                .map(let_binding)
+                .collect::<Result<Vec<_>, RispError>>()?;
+            for (name, _) in &bindings {
+                local.set(name, Node::Null);
+            }
+            for (name, value) in bindings {
+                let value = interp_node(value, &local)?;
+                local.set(name, value);
+            }
+        }
+    }
+    interp_tail_body(body, &local)
+}
+
+// (let loop ((i 0)) body...) binds loop to a function of the names, in a scope
+// of its own that the body can call it from, and calls it with the values.
+// Calls to loop in tail position loop without growing the stack
+fn interp_named_let(
+    name: &Symbol,
+    bindings: &[Node],
+    body: &[Node],
+    env: &Env,
+) -> Result<Step, RispError> {
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for binding in bindings {
+        let (param, value) = let_binding(binding)?;
+        params.push(Node::Word(param.clone()));
+        args.push(interp_node(value, env)?);
+    }
+    let local = env.child();
+    let closure = make_closure(&params, body, &local)?;
+    local.set(name, closure.clone());
+    match &closure {
+        Node::Closure(closure) => interp_tail_body(&closure.body, &bind_args(closure, args)?),
+        _ => unreachable!(),
+    }
+}
+
+fn let_binding(binding: &Node) -> Result<(&Symbol, &Node), RispError> {
+    if let Node::List(pair) = binding {
+        if let [Node::Word(name), value] = pair.as_slice() {
+            return Ok((name, value));
+        }
+    }
+    Err(RispError::RuntimeError(format!(
+        "invalid let binding {}",
+        binding
+    )))
+}
+
+// a call from a builtin such as map goes through several large Rust frames, so
+// it counts towards the recursion limit like a nested form does
+fn apply(closure: &Closure, args: Vec<Node>) -> Result<Node, RispError> {
+    let state = &closure.env.state;
+    let depth = state.depth.get();
+    if depth >= state.max_depth.get() {
+        return Err(RispError::RuntimeError(
+            "recursion limit exceeded".to_string(),
+        ));
+    }
+    state.depth.set(depth + 1);
+    let result = bind_args(closure, args)
+        .and_then(|env| interp_tail_body(&closure.body, &env))
+        .and_then(finish);
+    state.depth.set(depth);
+    result
+}
+
+// a fresh scope for a call, with each parameter bound to its argument
+fn bind_args(closure: &Closure, args: Vec<Node>) -> Result<Env, RispError> {
+    if args.len() != closure.params.len() {
+        return Err(RispError::RuntimeError(format!(
+            "expected {} arguments, got {}",
+            closure.params.len(),
+            args.len()
+        )));
+    }
+    let env = closure.env.child();
+    for (param, arg) in closure.params.iter().zip(args) {
+        env.set(param, arg);
+    }
+    Ok(env)
+}
+
+fn call(func: &Node, args: Vec<Node>, env: &Env) -> Result<Node, RispError> {
+    match func {
+        Node::Closure(closure) => apply(closure, args),
+        Node::Builtin(name) => call_builtin(name.as_str(), &args, env),
+        other => Err(RispError::RuntimeError(format!(
+            "{} is not callable",
+            other
+        ))),
+    }
+}
+
+// (apply f a b (c d)) calls f with the arguments a b c d
+fn interp_apply(args: &[Node], env: &Env) -> Result<Node, RispError> {
+    match args {
+        [func, fixed @ .., Node::List(rest)] => {
+            let mut call_args = fixed.to_vec();
+            call_args.extend(rest.iter().cloned());
+            call(func, call_args, env)
+        }
+        _ => Err(RispError::RuntimeError(
+            "apply expects a function and a list of arguments".to_string(),
+        )),
+    }
+}
+
+fn interp_map(args: &[Node], env: &Env) -> Result<Node, RispError> {
+    match args {
+        [func, Node::List(items)] => {
+            let mut results: Vec<Node> = Vec::new();
+            for item in items.iter() {
+                results.push(call(func, vec![item.clone()], env)?);
+            }
+            Ok(Node::List(Rc::new(results)))
+        }
+        [_, other] => Err(RispError::RuntimeError(format!(
+            "map expects a list, got {}",
+            other
+        ))),
+        _ => Err(RispError::RuntimeError(
+            "map expects a function and a list".to_string(),
+        )),
+    }
+}
+
+fn interp_filter(args: &[Node], env: &Env) -> Result<Node, RispError> {
+    match args {
+        [func, Node::List(items)] => {
+            let mut results: Vec<Node> = Vec::new();
+            for item in items.iter() {
+                if is_truthy(&call(func, vec![item.clone()], env)?) {
+                    results.push(item.clone());
+                }
+            }
+            Ok(Node::List(Rc::new(results)))
+        }
+        [_, other] => Err(RispError::RuntimeError(format!(
+            "filter expects a list, got {}",
+            other
+        ))),
+        _ => Err(RispError::RuntimeError(
+            "filter expects a predicate and a list".to_string(),
+        )),
+    }
+}
+
+// (fold f init list) calls (f acc item) for each item from left to right
+fn interp_fold(args: &[Node], env: &Env) -> Result<Node, RispError> {
+    match args {
+        [func, init, Node::List(items)] => {
+            let mut acc = init.clone();
+            for item in items.iter() {
+                acc = call(func, vec![acc, item.clone()], env)?;
+            }
+            Ok(acc)
+        }
+        [_, _, other] => Err(RispError::RuntimeError(format!(
+            "fold expects a list, got {}",
+            other
+        ))),
+        _ => Err(RispError::RuntimeError(
+            "fold expects a function, an initial value and a list".to_string(),
+        )),
+    }
+}
+
+// (sort list less?) orders the list by less?, which is given two items and
+// says whether the first goes before the second. Without one, the items must
+// be numbers and go in increasing order. Equal items keep their order
+fn interp_sort(args: &[Node], env: &Env) -> Result<Node, RispError> {
+    let (items, func) = match args {
+        [Node::List(items)] => (items.to_vec(), None),
+        [Node::List(items), func] => (items.to_vec(), Some(func.clone())),
+        [other] | [other, _] => {
+            return Err(RispError::RuntimeError(format!(
+                "sort expects a list, got {}",
+                other
+            )))
+        }
+        _ => {
+            return Err(RispError::RuntimeError(
+                "sort expects a list and an optional comparator".to_string(),
+            ))
+        }
+    };
+    let mut less = |left: &Node, right: &Node| match &func {
+        Some(func) => Ok(is_truthy(&call(
+            func,
+            vec![left.clone(), right.clone()],
+            env,
+        )?)),
+        None => match (left, right) {
+            (
+                Node::Number(_) | Node::BigInt(_) | Node::Ratio(_) | Node::Float(_),
+                Node::Number(_) | Node::BigInt(_) | Node::Ratio(_) | Node::Float(_),
+            ) => Ok(less_than(left, right)),
+            _ => Err(RispError::RuntimeError(format!(
+                "sort without a comparator expects numbers, got {} and {}",
+                left, right
+            ))),
+        },
+    };
+    Ok(Node::List(Rc::new(merge_sort(items, &mut less)?)))
+}
+
+// the comparator may fail, or not be consistent, which rules out slice::sort_by
+fn merge_sort(
+    mut items: Vec<Node>,
+    less: &mut impl FnMut(&Node, &Node) -> Result<bool, RispError>,
+) -> Result<Vec<Node>, RispError> {
+    if items.len() <= 1 {
+        return Ok(items);
+    }
+    let back = items.split_off(items.len() / 2);
+    let mut front = merge_sort(items, less)?.into_iter().peekable();
+    let mut back = merge_sort(back, less)?.into_iter().peekable();
+    let mut merged = Vec::new();
+    while let (Some(left), Some(right)) = (front.peek(), back.peek()) {
+        // taking from the back only when it is strictly less keeps the sort stable
+        if less(right, left)? {
+            merged.extend(back.next());
+        } else {
+            merged.extend(front.next());
+        }
+    }
+    merged.extend(front);
+    merged.extend(back);
+    Ok(merged)
+}
+
+fn eval_args(list: &[Node], env: &Env) -> Result<Vec<Node>, RispError> {
+    let mut args: Vec<Node> = Vec::new();
+    for node in &list[1..] {
+        args.push(interp_node(node, env)?);
+    }
+    Ok(args)
+}
+
+fn interp_list_op(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    match (op, args) {
+        ("car", [Node::Pair(cell)]) => Ok(cell.0.clone()),
+        ("cdr", [Node::Pair(cell)]) => Ok(cell.1.clone()),
+        ("car", [Node::List(l)]) => match l.first() {
+            Some(first) => Ok(first.clone()),
+            None => Err(RispError::RuntimeError("car of an empty list".to_string())),
+        },
+        ("cdr", [Node::List(l)]) => match l.split_first() {
+            Some((_, rest)) => Ok(Node::List(Rc::new(rest.to_vec()))),
+            None => Err(RispError::RuntimeError("cdr of an empty list".to_string())),
+        },
+        ("cons", [head, Node::List(l)]) => {
+            let mut new_list = vec![head.clone()];
+            new_list.extend(l.iter().cloned());
+            Ok(Node::List(Rc::new(new_list)))
+        }
+        // consing onto anything but a list makes an improper, dotted pair
+        ("cons", [head, tail]) => Ok(Node::Pair(Rc::new((head.clone(), tail.clone())))),
+        ("length", [Node::List(l)]) => Ok(Node::Number(l.len() as i64)),
+        ("reverse", [Node::List(l)]) => Ok(Node::List(Rc::new(l.iter().rev().cloned().collect()))),
+        ("nth", [Node::Number(n), Node::List(l)]) => {
+            match usize::try_from(*n).ok().and_then(|i| l.get(i)) {
+                Some(item) => Ok(item.clone()),
+                None => Err(RispError::RuntimeError(format!(
+                    "index {} out of range for a list of length {}",
+                    n,
+                    l.len()
+                ))),
+            }
+        }
+        // counts past the end of the list are clamped to its length, so taking
+        // too many gives the whole list and dropping too many gives ()
+        ("take" | "drop", [Node::Number(n), Node::List(l)])
+        | ("list-tail", [Node::List(l), Node::Number(n)]) => {
+            let n = match usize::try_from(*n) {
+                Ok(n) => n.min(l.len()),
+                Err(_) => {
+                    return Err(RispError::RuntimeError(format!(
+                        "{} expects a count that is not negative, got {}",
+                        op, n
+                    )))
+                }
+            };
+            let (taken, dropped) = l.split_at(n);
+            match op {
+                "take" => Ok(Node::List(Rc::new(taken.to_vec()))),
+                _ => Ok(Node::List(Rc::new(dropped.to_vec()))),
+            }
+        }
+        // both compare like equal?, and give false when nothing matches
+        ("member", [item, Node::List(l)]) => match l.iter().position(|node| node == item) {
+            Some(i) => Ok(Node::List(Rc::new(l[i..].to_vec()))),
+            None => Ok(Node::Bool(false)),
+        },
+        // entries are lists or dotted pairs whose first element is the key
+        ("assoc", [key, Node::List(l)]) => {
+            let entry = l.iter().find(|entry| match entry {
+                Node::List(entry) => entry.first() == Some(key),
+                Node::Pair(cell) => &cell.0 == key,
+                _ => false,
+            });
+            Ok(entry.cloned().unwrap_or(Node::Bool(false)))
+        }
+        ("append", lists) => {
+            let mut new_list: Vec<Node> = Vec::new();
+            for l in lists {
+                match l {
+                    Node::List(l) => new_list.extend(l.iter().cloned()),
+                    other => {
+                        return Err(RispError::RuntimeError(format!(
+                            "append expects lists, got {}",
+                            other
+                        )))
+                    }
+                }
+            }
+            Ok(Node::List(Rc::new(new_list)))
+        }
+        ("car" | "cdr", _) => Err(RispError::RuntimeError(format!(
+            "{} expects a single list or pair argument",
+            op
+        ))),
+        ("length" | "reverse", _) => Err(RispError::RuntimeError(format!(
+            "{} expects a single list argument",
+            op
+        ))),
+        ("nth", _) => Err(RispError::RuntimeError(
+            "nth expects an index and a list".to_string(),
+        )),
+        ("take" | "drop", _) => Err(RispError::RuntimeError(format!(
+            "{} expects a count and a list",
+            op
+        ))),
+        ("list-tail", _) => Err(RispError::RuntimeError(
+            "list-tail expects a list and a count".to_string(),
+        )),
+        ("member", _) => Err(RispError::RuntimeError(
+            "member expects an item and a list".to_string(),
+        )),
+        ("assoc", _) => Err(RispError::RuntimeError(
+            "assoc expects a key and a list of entries".to_string(),
+        )),
+        _ => Err(RispError::RuntimeError(
+            "cons expects 2 arguments".to_string(),
+        )),
+    }
+}
+
+// dicts are values like lists, so dict-set returns a new dict and leaves
+// the one it was given alone
+fn interp_dict_op(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    let key = |node: &Node| {
+        Key::from_node(node).ok_or_else(|| {
+            RispError::RuntimeError(format!(
+                "dict keys must be numbers, strings, symbols or bools, got {}",
+                node
+            ))
+        })
+    };
+    match (op, args) {
+        ("make-dict", []) => Ok(Node::Dict(Rc::new(BTreeMap::new()))),
+        ("dict-set", [Node::Dict(dict), k, v]) => {
+            let mut dict = dict.as_ref().clone();
+            dict.insert(key(k)?, v.clone());
+            Ok(Node::Dict(Rc::new(dict)))
+        }
+        // a missing key gives Null
+        ("dict-get", [Node::Dict(dict), k]) => {
+            Ok(dict.get(&key(k)?).cloned().unwrap_or(Node::Null))
+        }
+        ("make-dict", _) => Err(RispError::RuntimeError(
+            "make-dict expects no arguments".to_string(),
+        )),
+        ("dict-set", _) => Err(RispError::RuntimeError(
+            "dict-set expects a dict, a key and a value".to_string(),
+        )),
+        _ => Err(RispError::RuntimeError(
+            "dict-get expects a dict and a key".to_string(),
+        )),
+    }
+}
+
+fn interp_vector_op(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    if op == "vector" {
+        return Ok(Node::Vector(Rc::new(RefCell::new(args.to_vec()))));
+    }
+    let (items, rest) = match args.split_first() {
+        Some((Node::Vector(items), rest)) => (items, rest),
+        _ => return Err(RispError::RuntimeError(format!("{} expects a vector", op))),
+    };
+    let len = items.borrow().len();
+    let index = |n: &i64| match usize::try_from(*n) {
+        Ok(i) if i < len => Ok(i),
+        _ => Err(RispError::RuntimeError(format!(
+            "index {} out of range for a vector of length {}",
+            n, len
+        ))),
+    };
+    match (op, rest) {
+        ("vector-length", []) => Ok(Node::Number(len as i64)),
+        ("vector-ref", [Node::Number(n)]) => Ok(items.borrow()[index(n)?].clone()),
+        ("vector-set!", [Node::Number(n), value]) => {
+            items.borrow_mut()[index(n)?] = value.clone();
+            Ok(Node::Null)
+        }
+        ("vector-length", _) => Err(RispError::RuntimeError(
+            "vector-length expects a single vector".to_string(),
+        )),
+        ("vector-ref", _) => Err(RispError::RuntimeError(
+            "vector-ref expects a vector and an index".to_string(),
+        )),
+        _ => Err(RispError::RuntimeError(
+            "vector-set! expects a vector, an index and a value".to_string(),
+        )),
+    }
+}
+
+// equal? compares structure and types, so (equal? 1 1.0) is false, while
+// eq? only accepts atoms, closures, vectors and pairs by identity and the empty
+// list as equal, since lists have no identity of their own
+fn interp_equality(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    let is_equal = op == "equal?";
+    match args {
+        [left, right] if is_equal => Ok(Node::Bool(left == right)),
+        [Node::List(left), Node::List(right)] => {
+            Ok(Node::Bool(left.is_empty() && right.is_empty()))
+        }
+        [Node::Pair(left), Node::Pair(right)] => Ok(Node::Bool(Rc::ptr_eq(left, right))),
+        [Node::Dict(_), Node::Dict(_)] => Ok(Node::Bool(false)),
+        [Node::Vector(left), Node::Vector(right)] => Ok(Node::Bool(Rc::ptr_eq(left, right))),
+        [left, right] => Ok(Node::Bool(left == right)),
+        _ => Err(RispError::RuntimeError(format!(
+            "{} expects 2 arguments",
+            op
+        ))),
+    }
+}
+
+fn interp_type_predicate(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    let value = match args {
+        [value] => value.clone(),
+        _ => {
+            return Err(RispError::RuntimeError(format!(
+                "{} expects a single argument",
+                op
+            )))
+        }
+    };
+    Ok(Node::Bool(match op {
+        "number?" => matches!(
+            value,
+            Node::Number(_) | Node::BigInt(_) | Node::Ratio(_) | Node::Float(_)
+        ),
+        "list?" => matches!(value, Node::List(_)),
+        "symbol?" => matches!(value, Node::Word(_)),
+        // both Null and the empty list count as nothing
+        "null?" => match &value {
+            Node::Null => true,
+            Node::List(l) => l.is_empty(),
+            _ => false,
+        },
+        "bool?" => matches!(value, Node::Bool(_)),
+        "string?" => matches!(value, Node::Str(_)),
+        "procedure?" => matches!(value, Node::Closure(_) | Node::Builtin(_)),
+        "dict?" => matches!(value, Node::Dict(_)),
+        "vector?" => matches!(value, Node::Vector(_)),
+        "char?" => matches!(value, Node::Char(_)),
+        // a non-empty list is a chain of pairs too, as it would be in a cons-cell lisp
+        "pair?" => match &value {
+            Node::Pair(_) => true,
+            Node::List(l) => !l.is_empty(),
+            _ => false,
+        },
+        "proper-list?" => is_proper_list(&value),
+        _ => unreachable!(),
+    }))
+}
+
+// strings are indexed and measured in chars, not bytes
+fn interp_string_op(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    match (op, args) {
+        ("string-append", args) => {
+            let mut result = String::new();
+            for arg in args {
+                match arg {
+                    Node::Str(s) => result.push_str(s),
+                    other => {
+                        return Err(RispError::RuntimeError(format!(
+                            "string-append expects strings, got {}",
+                            other
+                        )))
+                    }
+                }
+            }
+            Ok(Node::Str(result))
+        }
+        ("string-length", [Node::Str(s)]) => Ok(Node::Number(s.chars().count() as i64)),
+        ("substring", [Node::Str(s), Node::Number(start), Node::Number(end)]) => {
+            let len = s.chars().count() as i64;
+            if *start < 0 || start > end || *end > len {
+                return Err(RispError::RuntimeError(format!(
+                    "substring range {}..{} out of bounds for length {}",
+                    start, end, len
+                )));
+            }
+            Ok(Node::Str(
+                s.chars()
+                    .skip(*start as usize)
+                    .take((end - start) as usize)
+                    .collect(),
+            ))
+        }
+        ("string-length", _) => Err(RispError::RuntimeError(
+            "string-length expects a single string".to_string(),
+        )),
+        _ => Err(RispError::RuntimeError(
+            "substring expects a string and two indices".to_string(),
+        )),
+    }
+}
+
+// (format "x = {}" x) puts each argument in place of a {}, in order, written
+// as display would. {{ and }} stand for literal braces
+fn interp_format(args: &[Node]) -> Result<Node, RispError> {
+    let (template, args) = match args.split_first() {
+        Some((Node::Str(template), args)) => (template, args),
+        _ => {
+            return Err(RispError::RuntimeError(
+                "format expects a template string and its arguments".to_string(),
+            ))
+        }
+    };
+    let mut result = String::new();
+    let given = args.len();
+    let mut args = args.iter();
+    let mut placeholders = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                result.push(ch);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                placeholders += 1;
+                if let Some(arg) = args.next() {
+                    result.push_str(&arg.to_string());
+                }
+            }
+            ('{' | '}', _) => {
+                return Err(RispError::RuntimeError(format!(
+                    "unmatched {} in format string, write {}{} for a literal one",
+                    ch, ch, ch
+                )))
+            }
+            _ => result.push(ch),
+        }
+    }
+    if placeholders != given {
+        return Err(RispError::RuntimeError(format!(
+            "format string has {} placeholders but was given {} arguments",
+            placeholders, given
+        )));
+    }
+    Ok(Node::Str(result))
+}
+
+// follows the cdrs in a loop rather than recursing, so long chains are fine
+fn is_proper_list(node: &Node) -> bool {
+    let mut rest = node;
+    loop {
+        match rest {
+            Node::List(_) => return true,
+            Node::Pair(cell) => rest = &cell.1,
+            _ => return false,
+        }
+    }
+}
+
+fn interp_conversion(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    match (op, args) {
+        (
+            "number->string",
+            [n @ (Node::Number(_) | Node::BigInt(_) | Node::Ratio(_) | Node::Float(_))],
+        ) => Ok(Node::Str(n.to_string())),
+        // accepts the same syntax as number literals, and gives Null for anything else
+        ("string->number", [Node::Str(s)]) => Ok(match parse_number(s) {
+            Some(Token::Number(n)) => Node::Number(n),
+            Some(Token::BigInt(n)) => Node::BigInt(n),
+            Some(Token::Float(n)) => Node::Float(n),
+            _ => Node::Null,
+        }),
+        ("string->list", [Node::Str(s)]) => {
+            Ok(Node::List(Rc::new(s.chars().map(Node::Char).collect())))
+        }
+        ("char->number", [Node::Char(c)]) => Ok(Node::Number(*c as i64)),
+        // surrogates and anything past 0x10ffff are not characters
+        ("number->char", [Node::Number(n)]) => {
+            match u32::try_from(*n).ok().and_then(char::from_u32) {
+                Some(c) => Ok(Node::Char(c)),
+                None => Err(RispError::RuntimeError(format!(
+                    "{} is not a valid character code",
+                    n
+                ))),
+            }
+        }
+        ("number->string", _) => Err(RispError::RuntimeError(
+            "number->string expects a single number".to_string(),
+        )),
+        ("char->number", _) => Err(RispError::RuntimeError(
+            "char->number expects a single character".to_string(),
+        )),
+        ("number->char", _) => Err(RispError::RuntimeError(
+            "number->char expects a single integer".to_string(),
+        )),
+        _ => Err(RispError::RuntimeError(format!(
+            "{} expects a single string",
+            op
+        ))),
+    }
+}
+
+// display writes its arguments as they are, print and println end the line
+fn interp_print(op: &str, args: &[Node]) -> Result<Node, RispError> {
+    let text = args
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut stdout = io::stdout();
+    let written = match op {
+        "display" => write!(stdout, "{}", text).and_then(|_| stdout.flush()),
+        _ => writeln!(stdout, "{}", text),
+    };
+    match written {
+        Ok(()) => Ok(Node::Null),
+        Err(e) => Err(RispError::RuntimeError(format!(
+            "failed to write output: {}",
+            e
+        ))),
+    }
+}
+
+// an empty line reads as "", only the end of input reads as Null
+fn interp_read_line(args: &[Node]) -> Result<Node, RispError> {
+    if !args.is_empty() {
+        return Err(RispError::RuntimeError(
+            "read-line expects no arguments".to_string(),
+        ));
+    }
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => Ok(Node::Null),
+        Ok(_) => {
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+            Ok(Node::Str(line))
+        }
+        Err(e) => Err(RispError::RuntimeError(format!(
+            "failed to read input: {}",
+            e
+        ))),
+    }
+}
+
+// failures quote the source of the forms, since that says more than their values
+fn interp_assert(list: &[Node], env: &Env) -> Result<Node, RispError> {
+    match list {
+        [Node::Word(w), form] if w == "assert" => {
+            if is_truthy(&interp_node(form, env)?) {
+                Ok(Node::Null)
+            } else {
+                Err(RispError::RuntimeError(format!(
+                    "assertion failed: {}",
+                    form
+                )))
+            }
+        }
+        [Node::Word(w), left, right] if w == "assert-eq" => {
+            let (l, r) = (interp_node(left, env)?, interp_node(right, env)?);
+            if l == r {
+                Ok(Node::Null)
+            } else {
+                Err(RispError::RuntimeError(format!(
+                    "assertion failed: {} is {}, but {} is {}",
+                    left, l, right, r
+                )))
+            }
+        }
+        [Node::Word(w), ..] if w == "assert" => Err(RispError::RuntimeError(
+            "assert expects a single expression".to_string(),
+        )),
+        _ => Err(RispError::RuntimeError(
+            "assert-eq expects 2 expressions".to_string(),
+        )),
+    }
+}
+
+fn interp_load(args: &[Node], env: &Env) -> Result<Node, RispError> {
+    let path = match args {
+        [Node::Str(path)] => PathBuf::from(path),
+        _ => {
+            return Err(RispError::RuntimeError(
+                "load expects a path string".to_string(),
+            ))
+        }
+    };
+    let resolved = match env
+        .state
+        .file
+        .borrow()
+        .as_ref()
+        .and_then(|file| file.parent())
+    {
+        Some(dir) => dir.join(path),
+        None => path,
+    };
+    load(resolved, env)
+}
+
+fn interp_word(word: &Symbol, env: &Env) -> Result<Node, RispError> {
+    match env.get(word) {
+        Some(value) => Ok(value),
+        // bindings shadow builtins of the same name when used as values
+        None if BUILTIN_NAMES.with(|names| names.contains(word)) => Ok(Node::Builtin(word.clone())),
+        None => Err(RispError::RuntimeError(format!("unbound symbol {}", word))),
+    }
+}
@@ -1,168 +1,156 @@
 use std::env;
-use std::fmt;
+use std::fmt::Display;
 use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+use std::thread;
 
-#[derive(Debug, PartialEq)]
-enum Token {
-    LParen,
-    RParen,
-    Number(i64),
-    Word(String),
-}
-
-#[derive(Debug, Clone, PartialEq)]
-enum Node {
-    Null,
-    List(Vec<Node>),
-    Number(i64),
-    Word(String),
-}
-
-impl fmt::Display for Node {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Node::Null => write!(f, "Null"),
-            Node::Number(n) => write!(f, "{}", n),
-            Node::Word(s) => write!(f, "{}", s),
-            Node::List(list) => {
-                write!(f, "(")?;
-                for (i, obj) in list.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, " ")?;
-                    }
-                    write!(f, "{}", obj)?;
-                }
-                write!(f, ")")
-            }
-        }
-    }
-}
+use risp::{Env, Node, RispError};
 
-fn lex(chars: &mut Vec<char>) -> Vec<Token> {
-    let mut tokens: Vec<Token> = Vec::new();
+// nesting in risp code is recursion in the interpreter, so run it on a stack
+// big enough for a deeper recursion limit than the library default, which has
+// to fit in the 8 MiB of an ordinary main thread
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+const MAX_DEPTH: usize = 10_000;
 
-    chars.reverse();
+// exit statuses: 0 on success, 1 when the program fails, 2 for bad usage,
+// and whatever a program passes to (exit n), which is from 0 to 255
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
 
-    let mut ch = chars.pop().unwrap();
-    while !chars.is_empty() {
-        match ch {
-            '(' => tokens.push(Token::LParen),
-            ')' => tokens.push(Token::RParen),
-            ';' => {
-                while !chars.is_empty() && ch != '\n' {
-                    ch = chars.pop().unwrap();
-                }
-                continue;
-            }
-            _ => {
-                let mut word = String::new();
-                if ch.is_numeric() {
-                    while ch.is_numeric() && !chars.is_empty() {
-                        word.push(ch);
-                        ch = chars.pop().unwrap();
-                    }
-                    tokens.push(Token::Number(word.parse::<i64>().unwrap()));
-                    continue;
-                } else if !ch.is_whitespace() {
-                    while !ch.is_whitespace() && !chars.is_empty() {
-                        word.push(ch);
-                        ch = chars.pop().unwrap();
-                    }
-                    tokens.push(Token::Word(word));
-                    continue;
-                }
-            }
-        }
-        ch = chars.pop().unwrap()
+fn main() {
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("failed to start the interpreter thread");
+    match interpreter.join() {
+        Ok(Ok(())) => {}
+        Ok(Err(RispError::Exit(code))) => process::exit(code),
+        Ok(Err(e)) => fail(e),
+        Err(_) => process::exit(EXIT_FAILURE),
     }
-
-    tokens.reverse();
-    tokens
 }
 
-fn parse(tokens: &mut Vec<Token>) -> Node {
-    let mut list: Vec<Node> = Vec::new();
-
-    while !tokens.is_empty() {
-        let token = tokens.pop().unwrap();
-        match token {
-            Token::LParen => list.push(parse(tokens)),
-            Token::RParen => break,
-            Token::Number(n) => list.push(Node::Number(n)),
-            Token::Word(w) => list.push(Node::Word(w)),
-        }
-    }
+const USAGE: &str = "Usage: risp [--ast | --tokens] [--no-prelude] [--trace] [file | - | -e expr]";
 
-    Node::List(list)
+enum Mode {
+    Run,
+    Ast,
+    Tokens,
 }
 
-fn interpret(program: &Node) {
-    let result = interp_node(program);
-    println!("{:?}", result);
+enum Input {
+    File(String),
+    Expr(String),
+    Stdin,
 }
 
-fn interp_node(node: &Node) -> Node {
-    match node {
-        Node::List(l) => interp_list(l),
-        Node::Word(w) => interp_word(w),
-        _ => node.clone(),
+fn run() -> Result<(), RispError> {
+    let mut mode = Mode::Run;
+    let mut input: Option<Input> = None;
+    let mut prelude = true;
+    let mut trace = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ast" => mode = Mode::Ast,
+            "--tokens" => mode = Mode::Tokens,
+            "--no-prelude" => prelude = false,
+            "--trace" => trace = true,
+            _ if input.is_some() => usage(),
+            "-e" => match args.next() {
+                Some(expr) => input = Some(Input::Expr(expr)),
+                None => usage(),
+            },
+            "-" => input = Some(Input::Stdin),
+            _ if arg.starts_with('-') => usage(),
+            _ => input = Some(Input::File(arg)),
+        }
     }
-}
-
-fn interp_list(list: &Vec<Node>) -> Node {
-    match &list[0] {
-        Node::Word(w) => match w.as_str() {
-            "+" => interp_binop(&list),
-            _ => Node::Null,
+    let env = Env::new();
+    env.set_max_depth(MAX_DEPTH);
+    if prelude {
+        risp::load_prelude(&env)?;
+    }
+    // turned on after the prelude, so only the program itself is traced
+    env.set_trace(trace);
+    let input = match (input, &mode) {
+        (Some(input), _) => input,
+        (None, Mode::Run) => return repl(&env),
+        (None, _) => usage(),
+    };
+
+    // files are run through load so that relative loads inside them resolve
+    if let (Mode::Run, Input::File(file_loc)) = (&mode, &input) {
+        println!("{}", risp::load(file_loc, &env)?);
+        return Ok(());
+    }
+    let source = match input {
+        Input::File(file_loc) => match fs::read_to_string(&file_loc) {
+            Ok(source) => source,
+            Err(e) => fail(format!("Failed to read {}: {}", file_loc, e)),
         },
-        _ => {
-            let mut new_list: Vec<Node> = Vec::new();
-            for node in list {
-                let result = interp_node(node);
-                if result != Node::Null {
-                    new_list.push(result);
+        Input::Expr(expr) => expr,
+        Input::Stdin => {
+            let mut source = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut source) {
+                fail(format!("Failed to read standard input: {}", e));
+            }
+            source
+        }
+    };
+
+    match mode {
+        Mode::Run => println!("{}", risp::eval(&risp::read(&source, MAX_DEPTH)?, &env)?),
+        Mode::Ast => match risp::read(&source, MAX_DEPTH)? {
+            Node::List(ref forms) => {
+                for form in forms.iter() {
+                    println!("{:#}", form);
                 }
             }
-            Node::List(new_list)
+            program => println!("{:#}", program),
+        },
+        Mode::Tokens => {
+            for (token, position) in risp::lex(source.chars())? {
+                println!("{}:{} {:?}", position.line, position.column, token);
+            }
         }
     }
+    Ok(())
 }
 
-fn interp_binop(list: &Vec<Node>) -> Node {
-    let left = &interp_node(&list[1]);
-    let right = &interp_node(&list[2]);
-    if let Node::Word(w) = &list[0] {
-        match w.as_str() {
-            "+" => match (left, right) {
-                (Node::Number(l), Node::Number(r)) => return Node::Number(l + r),
-                _ => Node::Null,
-            },
-            _ => Node::Null,
-        };
-    }
-    Node::Null
+fn fail(message: impl Display) -> ! {
+    eprintln!("{}", message);
+    process::exit(EXIT_FAILURE);
 }
 
-fn interp_word(word: &String) -> Node {
-    Node::Null
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(EXIT_USAGE);
 }
 
-fn main() {
-    let mut args = env::args();
-    if args.len() < 2 {
-        panic!("No file provided.");
-    } else {
-        let file_loc = args.nth(1).unwrap();
-
-        let mut chars: Vec<char> = fs::read_to_string(file_loc)
-            .expect("Failed to read the file.")
-            .replace("(", " ( ")
-            .replace(")", " ) ")
-            .chars()
-            .collect();
+// errors are reported and the loop goes on, only (exit n) ends the session early
+fn repl(env: &Env) -> Result<(), RispError> {
+    let stdin = io::stdin();
+    loop {
+        print!("risp> ");
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            // Ctrl-D
+            Ok(0) => {
+                println!();
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(e) => fail(format!("Failed to read input: {}", e)),
+        }
 
-        let mut tokens = lex(&mut chars);
-        let program = parse(&mut tokens);
-        interpret(&program);
+        match risp::read(&line, MAX_DEPTH).and_then(|program| risp::eval(&program, env)) {
+            Ok(result) => println!("{}", result),
+            Err(e @ RispError::Exit(_)) => return Err(e),
+            Err(e) => eprintln!("{}", e),
+        }
     }
 }
@@ -0,0 +1,154 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::bignum::BigInt;
+
+/// An exact fraction, always in lowest terms with a positive denominator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ratio {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Ratio {
+    /// Reduces numerator / denominator, giving None when the denominator is zero.
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Option<Ratio> {
+        if denominator.is_zero() {
+            return None;
+        }
+        let divisor = gcd(&numerator, &denominator);
+        let (mut numerator, _) = numerator.div_rem(&divisor)?;
+        let (mut denominator, _) = denominator.div_rem(&divisor)?;
+        if denominator < BigInt::from_i64(0) {
+            numerator = numerator.neg();
+            denominator = denominator.neg();
+        }
+        Some(Ratio {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn from_integer(n: BigInt) -> Ratio {
+        Ratio {
+            numerator: n,
+            denominator: BigInt::from_i64(1),
+        }
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == BigInt::from_i64(1)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator.to_f64() / self.denominator.to_f64()
+    }
+
+    pub fn abs(&self) -> Ratio {
+        Ratio {
+            numerator: self.numerator.abs(),
+            denominator: self.denominator.clone(),
+        }
+    }
+
+    pub fn add(&self, other: &Ratio) -> Ratio {
+        let numerator = self
+            .numerator
+            .mul(&other.denominator)
+            .add(&other.numerator.mul(&self.denominator));
+        Ratio::new(numerator, self.denominator.mul(&other.denominator)).unwrap()
+    }
+
+    pub fn sub(&self, other: &Ratio) -> Ratio {
+        self.add(&Ratio {
+            numerator: other.numerator.neg(),
+            denominator: other.denominator.clone(),
+        })
+    }
+
+    pub fn mul(&self, other: &Ratio) -> Ratio {
+        Ratio::new(
+            self.numerator.mul(&other.numerator),
+            self.denominator.mul(&other.denominator),
+        )
+        .unwrap()
+    }
+
+    /// Drops the fraction, rounding toward zero.
+    pub fn trunc(&self) -> BigInt {
+        self.numerator.div_rem(&self.denominator).unwrap().0
+    }
+
+    pub fn floor(&self) -> BigInt {
+        let trunc = self.trunc();
+        if self.is_integer() || self.numerator >= BigInt::from_i64(0) {
+            trunc
+        } else {
+            trunc.sub(&BigInt::from_i64(1))
+        }
+    }
+
+    pub fn ceil(&self) -> BigInt {
+        let trunc = self.trunc();
+        if self.is_integer() || self.numerator < BigInt::from_i64(0) {
+            trunc
+        } else {
+            trunc.add(&BigInt::from_i64(1))
+        }
+    }
+
+    /// Rounds to the nearest integer, and halfway cases away from zero.
+    pub fn round(&self) -> BigInt {
+        let sign = if self.numerator < BigInt::from_i64(0) {
+            -1
+        } else {
+            1
+        };
+        let half = Ratio::new(BigInt::from_i64(sign), BigInt::from_i64(2)).unwrap();
+        self.add(&half).trunc()
+    }
+
+    /// Gives None when other is zero.
+    pub fn div(&self, other: &Ratio) -> Option<Ratio> {
+        Ratio::new(
+            self.numerator.mul(&other.denominator),
+            self.denominator.mul(&other.numerator),
+        )
+    }
+}
+
+/// The greatest common divisor by Euclid's algorithm, never negative.
+pub(crate) fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while !b.is_zero() {
+        let (_, remainder) = a.div_rem(&b).unwrap();
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+// denominators are positive, so cross-multiplying keeps the order
+impl Ord for Ratio {
+    fn cmp(&self, other: &Ratio) -> Ordering {
+        self.numerator
+            .mul(&other.denominator)
+            .cmp(&other.numerator.mul(&self.denominator))
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Ratio) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
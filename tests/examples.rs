@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+// the example that is meant to fail, checked on its own
+const UNCAUGHT_ERROR: &str = "uncaught_error.risp";
+
+fn examples() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut examples: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("examples directory should be readable")
+        .map(|entry| entry.expect("examples should be listable").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "risp"))
+        .collect();
+    examples.sort();
+    examples
+}
+
+// stdin is empty, which is where examples/read_line.risp reads to the end of
+fn run(example: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_risp"))
+        .arg(example)
+        .stdin(Stdio::null())
+        .output()
+        .expect("risp should run")
+}
+
+#[test]
+fn every_example_runs_to_completion() {
+    let mut failures = Vec::new();
+    for example in examples() {
+        if example.ends_with(UNCAUGHT_ERROR) {
+            continue;
+        }
+        let output = run(&example);
+        if !output.status.success() {
+            failures.push(format!(
+                "{} exited with {}:\n{}",
+                example.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn uncaught_error_exits_with_status_1() {
+    let example = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join(UNCAUGHT_ERROR);
+    let output = run(&example);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("this is not caught"), "stderr: {}", stderr);
+    assert!(stdout.contains("before"), "stdout: {}", stdout);
+    assert!(!stdout.contains("never printed"), "stdout: {}", stdout);
+}
@@ -0,0 +1,78 @@
+use risp::{Position, Token};
+
+fn lex(source: &str) -> Vec<(Token, Position)> {
+    risp::lex(source.chars()).expect("source should lex")
+}
+
+fn at(line: usize, column: usize) -> Position {
+    Position { line, column }
+}
+
+#[test]
+fn tokens_come_in_source_order_with_positions() {
+    assert_eq!(
+        lex("(define x 'a) ; note\n\"s\" #\\a 1.5"),
+        vec![
+            (Token::LParen, at(1, 1)),
+            (Token::Word("define".to_string()), at(1, 2)),
+            (Token::Word("x".to_string()), at(1, 9)),
+            (Token::Quote, at(1, 11)),
+            (Token::Word("a".to_string()), at(1, 12)),
+            (Token::RParen, at(1, 13)),
+            (Token::Str("s".to_string()), at(2, 1)),
+            (Token::Char('a'), at(2, 5)),
+            (Token::Float(1.5), at(2, 9)),
+        ]
+    );
+}
+
+#[test]
+fn last_character_is_kept_without_a_trailing_newline() {
+    assert_eq!(
+        lex("(+ 1 2)"),
+        vec![
+            (Token::LParen, at(1, 1)),
+            (Token::Word("+".to_string()), at(1, 2)),
+            (Token::Number(1), at(1, 4)),
+            (Token::Number(2), at(1, 6)),
+            (Token::RParen, at(1, 7)),
+        ]
+    );
+    assert_eq!(lex("abc"), vec![(Token::Word("abc".to_string()), at(1, 1))]);
+    assert_eq!(lex("42"), vec![(Token::Number(42), at(1, 1))]);
+}
+
+#[test]
+fn malformed_numbers_are_lex_errors() {
+    for source in ["1__0", "5_", "1_.5", "0x_ff", "1e", "1e+", "1e_5"] {
+        match risp::lex(source.chars()) {
+            Err(risp::RispError::LexError(msg)) => {
+                assert_eq!(
+                    msg,
+                    format!("invalid number literal {} at line 1, column 1", source)
+                )
+            }
+            other => panic!("expected a lex error for {:?}, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn underscore_then_digits_is_a_symbol() {
+    assert_eq!(lex("_5"), vec![(Token::Word("_5".to_string()), at(1, 1))]);
+}
+
+#[test]
+fn comments_and_strings_end_the_atom_before_them() {
+    assert_eq!(
+        lex("x;c\n5;c\n(f\"s\")"),
+        vec![
+            (Token::Word("x".to_string()), at(1, 1)),
+            (Token::Number(5), at(2, 1)),
+            (Token::LParen, at(3, 1)),
+            (Token::Word("f".to_string()), at(3, 2)),
+            (Token::Str("s".to_string()), at(3, 3)),
+            (Token::RParen, at(3, 6)),
+        ]
+    );
+}
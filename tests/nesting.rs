@@ -0,0 +1,64 @@
+use risp::RispError;
+
+// a list nested 100,000 deep, far past the recursion limit, built by a loop
+const DEEP_LIST: &str = "(define x ()) (dotimes (i 100000) (set! x (list x)))";
+
+fn eval(source: &str) -> String {
+    match risp::eval_str(source) {
+        Ok(value) => value.to_string(),
+        Err(e) => panic!("{} failed: {}", source, e),
+    }
+}
+
+#[test]
+fn deeply_nested_data_compares_prints_and_drops() {
+    assert_eq!(
+        eval(&format!("{} (equal? x (list (car x)))", DEEP_LIST)),
+        "true"
+    );
+    assert_eq!(
+        eval(&format!(
+            "{} (string-length (format \"{{}}\" x))",
+            DEEP_LIST
+        )),
+        "200002"
+    );
+    assert_eq!(eval(&format!("{} (set! x 0)", DEEP_LIST)), "nil");
+    assert_eq!(
+        eval("(define v (vector)) (dotimes (i 100000) (set! v (vector v))) (equal? v v)"),
+        "true"
+    );
+    assert_eq!(
+        eval("(define p 0) (dotimes (i 100000) (set! p (cons p 1))) (pair? p)"),
+        "true"
+    );
+}
+
+fn nested_quote(depth: usize) -> String {
+    format!("(quote {}{})", "(".repeat(depth), ")".repeat(depth))
+}
+
+#[test]
+fn parse_nesting_follows_the_given_limit() {
+    assert!(risp::read(&nested_quote(600), 1_000).is_ok());
+    match risp::read(&nested_quote(600), 500) {
+        Err(RispError::ParseError(msg)) => {
+            assert_eq!(msg, "forms nested more than 500 deep at line 1, column 507")
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn self_containing_vectors_print_and_compare() {
+    let loop_of = |name: &str| format!("(define {0} (vector 1)) (vector-set! {0} 0 {0})", name);
+    assert_eq!(eval(&format!("{} v", loop_of("v"))), "#(#(...))");
+    assert_eq!(
+        eval(&format!(
+            "{} {} (list (equal? v w) (equal? v (vector 1)))",
+            loop_of("v"),
+            loop_of("w")
+        )),
+        "(true false)"
+    );
+}
@@ -0,0 +1,23 @@
+use risp::RispError;
+
+fn parse_error(source: &str) -> String {
+    match risp::read(source, risp::DEFAULT_MAX_DEPTH) {
+        Err(RispError::ParseError(msg)) => msg,
+        other => panic!("expected a parse error for {:?}, got {:?}", source, other),
+    }
+}
+
+#[test]
+fn unclosed_paren_names_where_it_opened() {
+    assert_eq!(parse_error("(+ 1 2"), "unclosed ( at line 1, column 1");
+    assert_eq!(
+        parse_error("(list 1\n  (+ 2 3)"),
+        "unclosed ( at line 1, column 1"
+    );
+}
+
+#[test]
+fn unexpected_paren_names_where_it_is() {
+    assert_eq!(parse_error("(+ 1 2))"), "unexpected ) at line 1, column 8");
+    assert_eq!(parse_error(")"), "unexpected ) at line 1, column 1");
+}
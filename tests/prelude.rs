@@ -0,0 +1,14 @@
+fn eval(source: &str) -> String {
+    match risp::eval_str(source) {
+        Ok(value) => value.to_string(),
+        Err(e) => panic!("{} failed: {}", source, e),
+    }
+}
+
+#[test]
+fn range_is_not_limited_by_the_recursion_limit() {
+    assert_eq!(eval("(range 0 5)"), "(0 1 2 3 4)");
+    assert_eq!(eval("(range 3 3)"), "()");
+    assert_eq!(eval("(range 5 2)"), "()");
+    assert_eq!(eval("(length (range 0 1000))"), "1000");
+}